@@ -0,0 +1,53 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+const EMAILS: &[&str] = &[
+    "lreilly@stanford.edu",
+    "Lreilly@Slac.Stanford.Edu",
+    "lreilly@soft-eng.strath.ac.uk",
+    "lee@gmail.com",
+    "lee@leerilly.net",
+    "john@america.edu",
+];
+
+fn bench_validate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("validate");
+    for email in EMAILS {
+        group.bench_function(*email, |b| b.iter(|| rswot::validate(black_box(*email))));
+    }
+    group.finish();
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for email in EMAILS {
+        group.bench_function(format!("Email/{email}"), |b| {
+            b.iter(|| rswot::Email::parse(black_box(email)))
+        });
+        group.bench_function(format!("EmailRef/{email}"), |b| {
+            b.iter(|| rswot::EmailRef::parse(black_box(email)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let emails: Vec<&str> = EMAILS.iter().copied().cycle().take(10_000).collect();
+    let mut group = c.benchmark_group("batch");
+    group.bench_function("validate", |b| {
+        b.iter(|| {
+            black_box(&emails)
+                .iter()
+                .map(|email| rswot::validate(*email))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("validate_batch", |b| {
+        b.iter(|| rswot::validate_batch(black_box(&emails).iter().copied()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_validate, bench_parse, bench_batch);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+use std::env;
+use std::path::Path;
+
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    // The header in `include` is checked against this one by the tests, the
+    // source tree is never written so it can be read-only or vendored
+    let config = cbindgen::Config::from_file(Path::new(&crate_dir).join("cbindgen.toml")).unwrap();
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Unable to generate bindings")
+        .write_to_file(Path::new(&out_dir).join("rswot.h"));
+
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
@@ -0,0 +1,153 @@
+#ifndef RSWOT_H
+#define RSWOT_H
+
+/* Generated with cbindgen, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Status of a validation
+typedef enum RswotStatus {
+  // Email is academic
+  RSWOT_STATUS_OK = 0,
+  // Email is not a valid address
+  RSWOT_STATUS_INVALID_EMAIL = 1,
+  // Email is from a known abusive domain
+  RSWOT_STATUS_ABUSE = 2,
+  // Email is marked as stop
+  RSWOT_STATUS_STOP = 3,
+  // Email is not academic
+  RSWOT_STATUS_NOT_ACADEMIC = 4,
+  // Email domain has no mail server
+  RSWOT_STATUS_NO_MAIL_SERVER = 5,
+  // Email is from a free mail provider
+  RSWOT_STATUS_FREE_MAIL_PROVIDER = 6,
+  // Email is from a disposable mail provider
+  RSWOT_STATUS_DISPOSABLE_PROVIDER = 7,
+  // Email is from an academic domain known for abuse
+  RSWOT_STATUS_ABUSED_ACADEMIC_DOMAIN = 8,
+  // Email domain looks like the domain of an institution
+  RSWOT_STATUS_SUSPECTED_IMPERSONATION = 9,
+  // Email domain embeds the domain of an institution or an academic suffix
+  RSWOT_STATUS_DECEPTIVE = 10,
+  // Internationalized email domain mixes scripts or uses a script that is not allowed
+  RSWOT_STATUS_MIXED_SCRIPT = 11,
+  // Internationalized email domain looks the same as an academic domain
+  RSWOT_STATUS_CONFUSABLE = 12,
+  // Email is a role account like `admin@` rather than an individual
+  RSWOT_STATUS_ROLE_ACCOUNT = 13,
+  // Email domain is a cohort subdomain that is not accepted, like `2011.cqut.edu.cn`
+  RSWOT_STATUS_COHORT = 14,
+  // A pointer argument is null or the email is not UTF-8
+  RSWOT_STATUS_INVALID_ARGUMENT = -1,
+} RswotStatus;
+
+// Affiliation hinted by the labels of the email domain
+typedef enum RswotAffiliation {
+  // No label gives a hint
+  RSWOT_AFFILIATION_UNKNOWN = 0,
+  // A label like `students`
+  RSWOT_AFFILIATION_STUDENT = 1,
+  // A label like `staff` or `faculty`
+  RSWOT_AFFILIATION_STAFF = 2,
+  // A label like `alumni` or a past graduation year
+  RSWOT_AFFILIATION_ALUMNI = 3,
+} RswotAffiliation;
+
+// Result of a successful validation, freed with `rswot_validation_free`
+typedef struct RswotValidation RswotValidation;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Validate a NUL-terminated email address.
+//
+// On success, `*out` is set to a validation that must be freed with
+// `rswot_validation_free`, otherwise it is set to null.
+//
+// # Safety
+//
+// `email` must be null or a valid NUL-terminated string and `out` must be null
+// or valid for writes.
+enum RswotStatus rswot_validate(const char *email, struct RswotValidation **out);
+
+// Human readable message of a status, as a static NUL-terminated string.
+//
+// The status is taken as an `int`, values that are not a status get the message
+// of `RSWOT_STATUS_INVALID_ARGUMENT`.
+const char *rswot_status_message(int status);
+
+// Normalized email address of a validation.
+//
+// The string is owned by the validation and valid until it is freed.
+//
+// # Safety
+//
+// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+const char *rswot_validation_email(const struct RswotValidation *validation);
+
+// Top-level domain of a validation.
+//
+// The string is owned by the validation and valid until it is freed.
+//
+// # Safety
+//
+// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+const char *rswot_validation_tld(const struct RswotValidation *validation);
+
+// Whether the email is from a known institution, as opposed to only being under
+// an academic suffix.
+//
+// # Safety
+//
+// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+bool rswot_validation_has_institution(const struct RswotValidation *validation);
+
+// Number of institution names of a validation.
+//
+// # Safety
+//
+// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+size_t rswot_validation_institution_count(const struct RswotValidation *validation);
+
+// Institution name at `index`, or null if out of bounds.
+//
+// The string is owned by the validation and valid until it is freed.
+//
+// # Safety
+//
+// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+const char *rswot_validation_institution_name(const struct RswotValidation *validation,
+                                              size_t index);
+
+// Affiliation hinted by the labels of the email domain, unknown for null.
+//
+// # Safety
+//
+// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+enum RswotAffiliation rswot_validation_affiliation(const struct RswotValidation *validation);
+
+// Whether the email is a role account like `admin@` rather than an individual.
+//
+// # Safety
+//
+// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+bool rswot_validation_is_role_account(const struct RswotValidation *validation);
+
+// Free a validation returned by `rswot_validate`, null is ignored.
+//
+// # Safety
+//
+// `validation` must be null or a pointer returned by `rswot_validate` that was not
+// already freed.
+void rswot_validation_free(struct RswotValidation *validation);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RSWOT_H */
@@ -0,0 +1,325 @@
+//! C bindings for [rswot](https://docs.rs/rswot).
+//!
+//! The header is `include/rswot.h`, generated with cbindgen in `OUT_DIR` on build
+//! and checked against the one in the source tree by the tests.
+
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::ptr;
+
+/// Status of a validation
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RswotStatus {
+    /// Email is academic
+    Ok = 0,
+    /// Email is not a valid address
+    InvalidEmail = 1,
+    /// Email is from a known abusive domain
+    Abuse = 2,
+    /// Email is marked as stop
+    Stop = 3,
+    /// Email is not academic
+    NotAcademic = 4,
+    /// Email domain has no mail server
+    NoMailServer = 5,
+    /// Email is from a free mail provider
+    FreeMailProvider = 6,
+    /// Email is from a disposable mail provider
+    DisposableProvider = 7,
+    /// Email is from an academic domain known for abuse
+    AbusedAcademicDomain = 8,
+    /// Email domain looks like the domain of an institution
+    SuspectedImpersonation = 9,
+    /// Email domain embeds the domain of an institution or an academic suffix
+    Deceptive = 10,
+    /// Internationalized email domain mixes scripts or uses a script that is not allowed
+    MixedScript = 11,
+    /// Internationalized email domain looks the same as an academic domain
+    Confusable = 12,
+    /// Email is a role account like `admin@` rather than an individual
+    RoleAccount = 13,
+    /// Email domain is a cohort subdomain that is not accepted, like `2011.cqut.edu.cn`
+    Cohort = 14,
+    /// A pointer argument is null or the email is not UTF-8
+    InvalidArgument = -1,
+}
+
+impl RswotStatus {
+    const ALL: [RswotStatus; 16] = [
+        RswotStatus::Ok,
+        RswotStatus::InvalidEmail,
+        RswotStatus::Abuse,
+        RswotStatus::Stop,
+        RswotStatus::NotAcademic,
+        RswotStatus::NoMailServer,
+        RswotStatus::FreeMailProvider,
+        RswotStatus::DisposableProvider,
+        RswotStatus::AbusedAcademicDomain,
+        RswotStatus::SuspectedImpersonation,
+        RswotStatus::Deceptive,
+        RswotStatus::MixedScript,
+        RswotStatus::Confusable,
+        RswotStatus::RoleAccount,
+        RswotStatus::Cohort,
+        RswotStatus::InvalidArgument,
+    ];
+
+    /// Status of a raw value from C, which may not be a status at all
+    fn from_raw(status: c_int) -> Option<RswotStatus> {
+        RswotStatus::ALL
+            .into_iter()
+            .find(|known| *known as c_int == status)
+    }
+}
+
+impl From<&rswot::Error> for RswotStatus {
+    fn from(error: &rswot::Error) -> RswotStatus {
+        match error {
+            rswot::Error::InvalidEmail => RswotStatus::InvalidEmail,
+            rswot::Error::Abuse => RswotStatus::Abuse,
+            rswot::Error::Stop => RswotStatus::Stop,
+            rswot::Error::NotAcademic => RswotStatus::NotAcademic,
+            rswot::Error::NoMailServer => RswotStatus::NoMailServer,
+            rswot::Error::FreeMailProvider => RswotStatus::FreeMailProvider,
+            rswot::Error::DisposableProvider => RswotStatus::DisposableProvider,
+            rswot::Error::AbusedAcademicDomain => RswotStatus::AbusedAcademicDomain,
+            rswot::Error::SuspectedImpersonation { .. } => RswotStatus::SuspectedImpersonation,
+            rswot::Error::Deceptive { .. } => RswotStatus::Deceptive,
+            rswot::Error::MixedScript => RswotStatus::MixedScript,
+            rswot::Error::Confusable { .. } => RswotStatus::Confusable,
+            rswot::Error::RoleAccount => RswotStatus::RoleAccount,
+            rswot::Error::Cohort { .. } => RswotStatus::Cohort,
+        }
+    }
+}
+
+/// Affiliation hinted by the labels of the email domain
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RswotAffiliation {
+    /// No label gives a hint
+    Unknown = 0,
+    /// A label like `students`
+    Student = 1,
+    /// A label like `staff` or `faculty`
+    Staff = 2,
+    /// A label like `alumni` or a past graduation year
+    Alumni = 3,
+}
+
+impl From<rswot::AffiliationHint> for RswotAffiliation {
+    fn from(affiliation: rswot::AffiliationHint) -> RswotAffiliation {
+        match affiliation {
+            rswot::AffiliationHint::Student => RswotAffiliation::Student,
+            rswot::AffiliationHint::Staff => RswotAffiliation::Staff,
+            rswot::AffiliationHint::Alumni => RswotAffiliation::Alumni,
+            rswot::AffiliationHint::Unknown => RswotAffiliation::Unknown,
+        }
+    }
+}
+
+/// Result of a successful validation, freed with `rswot_validation_free`
+pub struct RswotValidation {
+    email: CString,
+    tld: CString,
+    institution_names: Option<Vec<CString>>,
+    affiliation: RswotAffiliation,
+    role_account: bool,
+}
+
+impl From<rswot::Validation> for RswotValidation {
+    fn from(validation: rswot::Validation) -> RswotValidation {
+        // Emails and TLDs are ASCII and names come from text files, none contain NUL bytes
+        let to_c_string = |s: &str| CString::new(s).unwrap_or_default();
+        RswotValidation {
+            email: to_c_string(validation.email.as_str()),
+            tld: to_c_string(validation.tld.as_str()),
+            institution_names: validation
+                .institution_names
+                .map(|names| names.iter().map(to_c_string).collect()),
+            affiliation: validation.affiliation.into(),
+            role_account: validation.role_account,
+        }
+    }
+}
+
+/// Validate a NUL-terminated email address.
+///
+/// On success, `*out` is set to a validation that must be freed with
+/// `rswot_validation_free`, otherwise it is set to null.
+///
+/// # Safety
+///
+/// `email` must be null or a valid NUL-terminated string and `out` must be null
+/// or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rswot_validate(
+    email: *const c_char,
+    out: *mut *mut RswotValidation,
+) -> RswotStatus {
+    if email.is_null() || out.is_null() {
+        return RswotStatus::InvalidArgument;
+    }
+    unsafe { *out = ptr::null_mut() };
+
+    let Ok(email) = unsafe { CStr::from_ptr(email) }.to_str() else {
+        return RswotStatus::InvalidArgument;
+    };
+
+    match rswot::validate(email) {
+        Ok(validation) => {
+            let validation = Box::new(RswotValidation::from(validation));
+            unsafe { *out = Box::into_raw(validation) };
+            RswotStatus::Ok
+        }
+        Err(error) => RswotStatus::from(&error),
+    }
+}
+
+/// Human readable message of a status, as a static NUL-terminated string.
+///
+/// The status is taken as an `int`, values that are not a status get the message
+/// of `RSWOT_STATUS_INVALID_ARGUMENT`.
+#[unsafe(no_mangle)]
+pub extern "C" fn rswot_status_message(status: c_int) -> *const c_char {
+    let status = RswotStatus::from_raw(status).unwrap_or(RswotStatus::InvalidArgument);
+    let message: &CStr = match status {
+        RswotStatus::Ok => c"Email is academic",
+        RswotStatus::InvalidEmail => c"Invalid email",
+        RswotStatus::Abuse => c"Email is from a known abusive domain",
+        RswotStatus::Stop => c"Email is marked as stop",
+        RswotStatus::NotAcademic => c"Email is not academic",
+        RswotStatus::NoMailServer => c"Email domain has no mail server",
+        RswotStatus::FreeMailProvider => c"Email is from a free mail provider",
+        RswotStatus::DisposableProvider => c"Email is from a disposable mail provider",
+        RswotStatus::AbusedAcademicDomain => c"Email is from an academic domain known for abuse",
+        RswotStatus::SuspectedImpersonation => {
+            c"Email domain looks like the domain of an institution"
+        }
+        RswotStatus::Deceptive => {
+            c"Email domain embeds the domain of an institution or an academic suffix"
+        }
+        RswotStatus::MixedScript => {
+            c"Email domain mixes scripts or uses a script that is not allowed"
+        }
+        RswotStatus::Confusable => c"Email domain is confusable with an academic domain",
+        RswotStatus::RoleAccount => c"Email is a role account, not an individual",
+        RswotStatus::Cohort => c"Email domain is for a cohort that is not accepted",
+        RswotStatus::InvalidArgument => c"Invalid argument",
+    };
+    message.as_ptr()
+}
+
+/// Normalized email address of a validation.
+///
+/// The string is owned by the validation and valid until it is freed.
+///
+/// # Safety
+///
+/// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rswot_validation_email(
+    validation: *const RswotValidation,
+) -> *const c_char {
+    match unsafe { validation.as_ref() } {
+        Some(validation) => validation.email.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Top-level domain of a validation.
+///
+/// The string is owned by the validation and valid until it is freed.
+///
+/// # Safety
+///
+/// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rswot_validation_tld(validation: *const RswotValidation) -> *const c_char {
+    match unsafe { validation.as_ref() } {
+        Some(validation) => validation.tld.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Whether the email is from a known institution, as opposed to only being under
+/// an academic suffix.
+///
+/// # Safety
+///
+/// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rswot_validation_has_institution(
+    validation: *const RswotValidation,
+) -> bool {
+    unsafe { validation.as_ref() }.is_some_and(|v| v.institution_names.is_some())
+}
+
+/// Number of institution names of a validation.
+///
+/// # Safety
+///
+/// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rswot_validation_institution_count(
+    validation: *const RswotValidation,
+) -> usize {
+    unsafe { validation.as_ref() }
+        .and_then(|v| v.institution_names.as_ref())
+        .map_or(0, Vec::len)
+}
+
+/// Institution name at `index`, or null if out of bounds.
+///
+/// The string is owned by the validation and valid until it is freed.
+///
+/// # Safety
+///
+/// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rswot_validation_institution_name(
+    validation: *const RswotValidation,
+    index: usize,
+) -> *const c_char {
+    unsafe { validation.as_ref() }
+        .and_then(|v| v.institution_names.as_ref())
+        .and_then(|names| names.get(index))
+        .map_or(ptr::null(), |name| name.as_ptr())
+}
+
+/// Affiliation hinted by the labels of the email domain, unknown for null.
+///
+/// # Safety
+///
+/// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rswot_validation_affiliation(
+    validation: *const RswotValidation,
+) -> RswotAffiliation {
+    unsafe { validation.as_ref() }.map_or(RswotAffiliation::Unknown, |v| v.affiliation)
+}
+
+/// Whether the email is a role account like `admin@` rather than an individual.
+///
+/// # Safety
+///
+/// `validation` must be a pointer returned by `rswot_validate` that was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rswot_validation_is_role_account(
+    validation: *const RswotValidation,
+) -> bool {
+    unsafe { validation.as_ref() }.is_some_and(|v| v.role_account)
+}
+
+/// Free a validation returned by `rswot_validate`, null is ignored.
+///
+/// # Safety
+///
+/// `validation` must be null or a pointer returned by `rswot_validate` that was not
+/// already freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rswot_validation_free(validation: *mut RswotValidation) {
+    if !validation.is_null() {
+        drop(unsafe { Box::from_raw(validation) });
+    }
+}
@@ -0,0 +1,34 @@
+//! Compile `tests/test.c` against the static library and run it.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_c_program() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // Integration tests live in `target/<profile>/deps`, next to the libraries
+    let exe = env::current_exe().unwrap();
+    let lib_dir = exe.parent().unwrap().parent().unwrap();
+    let program = Path::new(env!("CARGO_TARGET_TMPDIR")).join("rswot_test");
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(manifest_dir.join("tests").join("test.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(lib_dir.join("librswot_ffi.a"))
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("Unable to run the C compiler");
+    assert!(status.success(), "C compilation failed");
+
+    let output = Command::new(&program).output().unwrap();
+    assert!(
+        output.status.success(),
+        "C test failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
@@ -0,0 +1,19 @@
+//! Check that the header in the source tree is the one generated by cbindgen.
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_header_up_to_date() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/rswot.h"));
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("include")
+        .join("rswot.h");
+    let checked_in = fs::read_to_string(&path).unwrap();
+    assert!(
+        checked_in == generated,
+        "{} is out of date, copy it from {}/rswot.h",
+        path.display(),
+        env!("OUT_DIR")
+    );
+}
@@ -0,0 +1,63 @@
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "rswot.h"
+
+static void test_validate(void) {
+  RswotValidation *validation = NULL;
+  RswotStatus status = rswot_validate("Lreilly@Stanford.edu", &validation);
+  assert(status == RSWOT_STATUS_OK);
+  assert(validation != NULL);
+  assert(strcmp(rswot_validation_email(validation), "lreilly@stanford.edu") == 0);
+  assert(strcmp(rswot_validation_tld(validation), "edu") == 0);
+  assert(rswot_validation_has_institution(validation));
+  assert(rswot_validation_institution_count(validation) == 1);
+  assert(strcmp(rswot_validation_institution_name(validation, 0), "Stanford University") == 0);
+  assert(rswot_validation_institution_name(validation, 1) == NULL);
+  assert(rswot_validation_affiliation(validation) == RSWOT_AFFILIATION_UNKNOWN);
+  assert(!rswot_validation_is_role_account(validation));
+  rswot_validation_free(validation);
+
+  assert(rswot_validate("lee@students.uonbi.ac.ke", &validation) == RSWOT_STATUS_OK);
+  assert(rswot_validation_affiliation(validation) == RSWOT_AFFILIATION_STUDENT);
+  rswot_validation_free(validation);
+
+  assert(rswot_validate("admin@stanford.edu", &validation) == RSWOT_STATUS_OK);
+  assert(rswot_validation_is_role_account(validation));
+  rswot_validation_free(validation);
+}
+
+static void test_validate_suffix(void) {
+  RswotValidation *validation = NULL;
+  assert(rswot_validate("lee@uct.ac.za", &validation) == RSWOT_STATUS_OK);
+  assert(strcmp(rswot_validation_tld(validation), "za") == 0);
+  assert(!rswot_validation_has_institution(validation));
+  assert(rswot_validation_institution_count(validation) == 0);
+  rswot_validation_free(validation);
+}
+
+static void test_validate_errors(void) {
+  RswotValidation *validation = NULL;
+  assert(rswot_validate("lee@gmail.com", &validation) == RSWOT_STATUS_FREE_MAIL_PROVIDER);
+  assert(rswot_validate("lee@mailinator.com", &validation) == RSWOT_STATUS_DISPOSABLE_PROVIDER);
+  assert(rswot_validate("john@si.edu", &validation) == RSWOT_STATUS_ABUSED_ACADEMIC_DOMAIN);
+  assert(validation == NULL);
+  assert(rswot_validate("lee@leerilly.net", &validation) == RSWOT_STATUS_NOT_ACADEMIC);
+  assert(rswot_validate("lee@stanford.edu.com", &validation) == RSWOT_STATUS_DECEPTIVE);
+  assert(rswot_validate("john@america.edu", &validation) == RSWOT_STATUS_STOP);
+  assert(rswot_validate("the", &validation) == RSWOT_STATUS_INVALID_EMAIL);
+  assert(rswot_validate(NULL, &validation) == RSWOT_STATUS_INVALID_ARGUMENT);
+  assert(rswot_validate("lee@stanford.edu", NULL) == RSWOT_STATUS_INVALID_ARGUMENT);
+  assert(strcmp(rswot_status_message(RSWOT_STATUS_NOT_ACADEMIC), "Email is not academic") == 0);
+  assert(strcmp(rswot_status_message(42), "Invalid argument") == 0);
+  rswot_validation_free(NULL);
+}
+
+int main(void) {
+  test_validate();
+  test_validate_suffix();
+  test_validate_errors();
+  printf("ok\n");
+  return 0;
+}
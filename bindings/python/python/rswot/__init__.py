@@ -0,0 +1,47 @@
+"""Identify email addresses that belong to colleges or universities."""
+
+from ._rswot import (
+    AbusedAcademicDomainError,
+    AbuseError,
+    CohortError,
+    ConfusableError,
+    DeceptiveError,
+    DisposableProviderError,
+    Email,
+    FreeMailProviderError,
+    InvalidEmailError,
+    MixedScriptError,
+    NoMailServerError,
+    NotAcademicError,
+    RoleAccountError,
+    RswotError,
+    StopError,
+    SuspectedImpersonationError,
+    Validation,
+    institution_names,
+    validate,
+    validate_many,
+)
+
+__all__ = [
+    "AbusedAcademicDomainError",
+    "AbuseError",
+    "CohortError",
+    "ConfusableError",
+    "DeceptiveError",
+    "DisposableProviderError",
+    "Email",
+    "FreeMailProviderError",
+    "InvalidEmailError",
+    "MixedScriptError",
+    "NoMailServerError",
+    "NotAcademicError",
+    "RoleAccountError",
+    "RswotError",
+    "StopError",
+    "SuspectedImpersonationError",
+    "Validation",
+    "institution_names",
+    "validate",
+    "validate_many",
+]
@@ -0,0 +1,49 @@
+from typing import Optional, Union
+
+class RswotError(ValueError): ...
+class InvalidEmailError(RswotError): ...
+class AbuseError(RswotError): ...
+class FreeMailProviderError(AbuseError): ...
+class DisposableProviderError(AbuseError): ...
+class AbusedAcademicDomainError(AbuseError): ...
+class StopError(RswotError): ...
+class NotAcademicError(RswotError): ...
+class NoMailServerError(RswotError): ...
+class SuspectedImpersonationError(RswotError):
+    target: str
+
+class DeceptiveError(RswotError):
+    target: str
+
+class MixedScriptError(RswotError): ...
+
+class ConfusableError(RswotError):
+    target: str
+
+class RoleAccountError(RswotError): ...
+
+class CohortError(RswotError):
+    year: int
+
+class Email:
+    def __init__(self, email: str) -> None: ...
+    @property
+    def user(self) -> str: ...
+    @property
+    def domain(self) -> str: ...
+
+class Validation:
+    @property
+    def email(self) -> Email: ...
+    @property
+    def tld(self) -> str: ...
+    @property
+    def institution_names(self) -> Optional[list[str]]: ...
+    @property
+    def affiliation(self) -> str: ...
+    @property
+    def role_account(self) -> bool: ...
+
+def validate(email: str) -> Validation: ...
+def validate_many(emails: list[str]) -> list[Union[Validation, RswotError]]: ...
+def institution_names(domain: str) -> Optional[list[str]]: ...
@@ -0,0 +1,270 @@
+//! Python bindings for [rswot](https://docs.rs/rswot).
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+create_exception!(_rswot, RswotError, PyValueError, "Email was rejected");
+create_exception!(_rswot, InvalidEmailError, RswotError, "Invalid email");
+create_exception!(
+    _rswot,
+    AbuseError,
+    RswotError,
+    "Email is from a known abusive domain"
+);
+create_exception!(_rswot, StopError, RswotError, "Email is marked as stop");
+create_exception!(
+    _rswot,
+    NotAcademicError,
+    RswotError,
+    "Email is not academic"
+);
+create_exception!(
+    _rswot,
+    FreeMailProviderError,
+    AbuseError,
+    "Email is from a free mail provider"
+);
+create_exception!(
+    _rswot,
+    DisposableProviderError,
+    AbuseError,
+    "Email is from a disposable mail provider"
+);
+create_exception!(
+    _rswot,
+    AbusedAcademicDomainError,
+    AbuseError,
+    "Email is from an academic domain known for abuse"
+);
+create_exception!(
+    _rswot,
+    SuspectedImpersonationError,
+    RswotError,
+    "Email domain looks like the domain of an institution"
+);
+create_exception!(
+    _rswot,
+    DeceptiveError,
+    RswotError,
+    "Email domain embeds the domain of an institution or an academic suffix"
+);
+create_exception!(
+    _rswot,
+    MixedScriptError,
+    RswotError,
+    "Email domain mixes scripts or uses a script that is not allowed"
+);
+create_exception!(
+    _rswot,
+    ConfusableError,
+    RswotError,
+    "Email domain is confusable with an academic domain"
+);
+create_exception!(
+    _rswot,
+    RoleAccountError,
+    RswotError,
+    "Email is a role account, not an individual"
+);
+create_exception!(
+    _rswot,
+    CohortError,
+    RswotError,
+    "Email domain is for a cohort that is not accepted"
+);
+create_exception!(
+    _rswot,
+    NoMailServerError,
+    RswotError,
+    "Email domain has no mail server"
+);
+
+/// Exception of an error, with its `target` or `year` as attributes
+fn to_py_err(error: rswot::Error) -> PyErr {
+    let message = error.to_string();
+    let err = match &error {
+        rswot::Error::InvalidEmail => InvalidEmailError::new_err(message),
+        rswot::Error::Abuse => AbuseError::new_err(message),
+        rswot::Error::Stop => StopError::new_err(message),
+        rswot::Error::NotAcademic => NotAcademicError::new_err(message),
+        rswot::Error::NoMailServer => NoMailServerError::new_err(message),
+        rswot::Error::FreeMailProvider => FreeMailProviderError::new_err(message),
+        rswot::Error::DisposableProvider => DisposableProviderError::new_err(message),
+        rswot::Error::AbusedAcademicDomain => AbusedAcademicDomainError::new_err(message),
+        rswot::Error::SuspectedImpersonation { .. } => {
+            SuspectedImpersonationError::new_err(message)
+        }
+        rswot::Error::Deceptive { .. } => DeceptiveError::new_err(message),
+        rswot::Error::MixedScript => MixedScriptError::new_err(message),
+        rswot::Error::Confusable { .. } => ConfusableError::new_err(message),
+        rswot::Error::RoleAccount => RoleAccountError::new_err(message),
+        rswot::Error::Cohort { .. } => CohortError::new_err(message),
+    };
+    Python::attach(|py| {
+        let value = err.value(py);
+        match error {
+            rswot::Error::SuspectedImpersonation { target }
+            | rswot::Error::Deceptive { target }
+            | rswot::Error::Confusable { target } => value.setattr("target", target),
+            rswot::Error::Cohort { year } => value.setattr("year", year),
+            _ => Ok(()),
+        }
+        .expect("exceptions take attributes");
+    });
+    err
+}
+
+/// Email address
+#[pyclass(frozen, eq, hash, skip_from_py_object, module = "rswot")]
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Email(rswot::Email);
+
+#[pymethods]
+impl Email {
+    #[new]
+    fn new(email: &str) -> PyResult<Email> {
+        rswot::Email::parse(email).map(Email).map_err(to_py_err)
+    }
+
+    #[getter]
+    fn user(&self) -> &str {
+        self.0.user()
+    }
+
+    #[getter]
+    fn domain(&self) -> &str {
+        self.0.domain()
+    }
+
+    fn __str__(&self) -> &str {
+        self.0.as_str()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Email({:?})", self.0.as_str())
+    }
+}
+
+/// Result of validation
+#[pyclass(frozen, eq, skip_from_py_object, module = "rswot")]
+#[derive(Clone, PartialEq, Eq)]
+struct Validation(rswot::Validation);
+
+#[pymethods]
+impl Validation {
+    /// Email address that was validated
+    #[getter]
+    fn email(&self) -> Email {
+        Email(self.0.email.clone())
+    }
+
+    /// Top-level domain of email
+    #[getter]
+    fn tld(&self) -> &str {
+        self.0.tld.as_str()
+    }
+
+    /// Names of the institution that the email is from
+    #[getter]
+    fn institution_names(&self) -> Option<Vec<&str>> {
+        self.0
+            .institution_names
+            .as_ref()
+            .map(|names| names.iter().collect())
+    }
+
+    /// Affiliation hinted by the labels of the domain: "student", "staff",
+    /// "alumni" or "unknown"
+    #[getter]
+    fn affiliation(&self) -> &'static str {
+        self.0.affiliation.as_str()
+    }
+
+    /// Whether the email is a role account like "admin@" rather than an individual
+    #[getter]
+    fn role_account(&self) -> bool {
+        self.0.role_account
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Validation(email={:?}, tld={:?}, institution_names={:?})",
+            self.0.email.as_str(),
+            self.0.tld.as_str(),
+            self.0.institution_names
+        )
+    }
+}
+
+/// Validate an email address, raises a subclass of `RswotError` if it is rejected.
+#[pyfunction]
+fn validate(email: &str) -> PyResult<Validation> {
+    rswot::validate(email).map(Validation).map_err(to_py_err)
+}
+
+/// Validate a list of email addresses.
+///
+/// Returns a list of the same length with a `Validation` for accepted emails
+/// and an `RswotError` instance (not raised) for rejected ones.
+#[pyfunction]
+fn validate_many(py: Python<'_>, emails: Vec<String>) -> PyResult<Vec<Py<PyAny>>> {
+    let results: Vec<rswot::Result<rswot::Validation>> = py.detach(|| {
+        emails
+            .iter()
+            .map(|email| rswot::validate(email.as_str()))
+            .collect()
+    });
+
+    results
+        .into_iter()
+        .map(|result| match result {
+            Ok(validation) => Ok(Py::new(py, Validation(validation))?.into_any()),
+            Err(error) => Ok(to_py_err(error).into_value(py).into_any()),
+        })
+        .collect()
+}
+
+/// Look up the names of the institution that a domain belongs to.
+#[pyfunction]
+fn institution_names(domain: &str) -> Option<Vec<&'static str>> {
+    rswot::institution_names(domain).map(<[_]>::to_vec)
+}
+
+#[pymodule]
+fn _rswot(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add_class::<Email>()?;
+    m.add_class::<Validation>()?;
+    m.add_function(wrap_pyfunction!(validate, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_many, m)?)?;
+    m.add_function(wrap_pyfunction!(institution_names, m)?)?;
+    m.add("RswotError", py.get_type::<RswotError>())?;
+    m.add("InvalidEmailError", py.get_type::<InvalidEmailError>())?;
+    m.add("AbuseError", py.get_type::<AbuseError>())?;
+    m.add("StopError", py.get_type::<StopError>())?;
+    m.add("NotAcademicError", py.get_type::<NotAcademicError>())?;
+    m.add("NoMailServerError", py.get_type::<NoMailServerError>())?;
+    m.add(
+        "FreeMailProviderError",
+        py.get_type::<FreeMailProviderError>(),
+    )?;
+    m.add(
+        "DisposableProviderError",
+        py.get_type::<DisposableProviderError>(),
+    )?;
+    m.add(
+        "AbusedAcademicDomainError",
+        py.get_type::<AbusedAcademicDomainError>(),
+    )?;
+    m.add(
+        "SuspectedImpersonationError",
+        py.get_type::<SuspectedImpersonationError>(),
+    )?;
+    m.add("DeceptiveError", py.get_type::<DeceptiveError>())?;
+    m.add("MixedScriptError", py.get_type::<MixedScriptError>())?;
+    m.add("ConfusableError", py.get_type::<ConfusableError>())?;
+    m.add("RoleAccountError", py.get_type::<RoleAccountError>())?;
+    m.add("CohortError", py.get_type::<CohortError>())?;
+    Ok(())
+}
@@ -0,0 +1,80 @@
+import pytest
+
+import rswot
+
+
+def test_validate():
+    validation = rswot.validate("Lreilly@Stanford.edu")
+    assert str(validation.email) == "lreilly@stanford.edu"
+    assert validation.tld == "edu"
+    assert validation.institution_names == ["Stanford University"]
+    assert validation.affiliation == "unknown"
+    assert not validation.role_account
+
+
+def test_validate_affiliation():
+    assert rswot.validate("lee@students.uonbi.ac.ke").affiliation == "student"
+    assert rswot.validate("lee@alumni.fh-aachen.de").affiliation == "alumni"
+
+
+def test_validate_role_account():
+    assert rswot.validate("no-reply@stanford.edu").role_account
+
+
+def test_validate_suffix():
+    validation = rswot.validate("lee@uct.ac.za")
+    assert validation.tld == "za"
+    assert validation.institution_names is None
+
+
+@pytest.mark.parametrize(
+    ("email", "error"),
+    [
+        ("the", rswot.InvalidEmailError),
+        ("lee@gmail.com", rswot.FreeMailProviderError),
+        ("lee@mailinator.com", rswot.DisposableProviderError),
+        ("john@si.edu", rswot.AbusedAcademicDomainError),
+        ("john@america.edu", rswot.StopError),
+        ("lee@leerilly.net", rswot.NotAcademicError),
+        ("lee@stanford.edu.com", rswot.DeceptiveError),
+    ],
+)
+def test_validate_errors(email, error):
+    with pytest.raises(error):
+        rswot.validate(email)
+    assert issubclass(error, rswot.RswotError)
+    assert issubclass(error, ValueError)
+
+
+def test_validate_error_target():
+    with pytest.raises(rswot.DeceptiveError) as error:
+        rswot.validate("lee@stanford.edu.com")
+    assert error.value.target == "stanford.edu"
+
+    results = rswot.validate_many(["lee@stanford.edu.com", "lee@gmail.com"])
+    assert results[0].target == "stanford.edu"
+    assert not hasattr(results[1], "target")
+
+
+def test_validate_many():
+    results = rswot.validate_many(["lee@ugr.es", "lee@gmail.com", "the"])
+    assert len(results) == 3
+    assert isinstance(results[0], rswot.Validation)
+    assert isinstance(results[1], rswot.FreeMailProviderError)
+    assert isinstance(results[1], rswot.AbuseError)
+    assert isinstance(results[2], rswot.InvalidEmailError)
+
+
+def test_email():
+    email = rswot.Email(" Orhanbalci@ku.edu.tr")
+    assert email.user == "orhanbalci"
+    assert email.domain == "ku.edu.tr"
+    assert email == rswot.Email("orhanbalci@ku.edu.tr")
+    assert len({email, rswot.Email("orhanbalci@ku.edu.tr")}) == 1
+    with pytest.raises(rswot.InvalidEmailError):
+        rswot.Email("orhanbalci")
+
+
+def test_institution_names():
+    assert rswot.institution_names("harvard.edu") == ["Harvard University"]
+    assert rswot.institution_names("leerilly.net") is None
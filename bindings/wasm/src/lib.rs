@@ -0,0 +1,172 @@
+//! WebAssembly bindings for [rswot](https://docs.rs/rswot).
+
+use wasm_bindgen::prelude::*;
+
+/// Reason an email was rejected
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    InvalidEmail,
+    Abuse,
+    Stop,
+    NotAcademic,
+    NoMailServer,
+    FreeMailProvider,
+    DisposableProvider,
+    AbusedAcademicDomain,
+    SuspectedImpersonation,
+    Deceptive,
+    MixedScript,
+    Confusable,
+    RoleAccount,
+    Cohort,
+}
+
+/// Error thrown when an email is rejected
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    kind: ErrorKind,
+    message: String,
+    target: Option<String>,
+}
+
+#[wasm_bindgen]
+impl ValidationError {
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    /// Domain of the impersonated institution, for `SuspectedImpersonation`,
+    /// or the embedded institution domain or academic suffix, for `Deceptive`,
+    /// or the academic domain that looks the same, for `Confusable`
+    #[wasm_bindgen(getter)]
+    pub fn target(&self) -> Option<String> {
+        self.target.clone()
+    }
+}
+
+impl From<rswot::Error> for ValidationError {
+    fn from(error: rswot::Error) -> ValidationError {
+        let message = error.to_string();
+        let (kind, target) = match error {
+            rswot::Error::InvalidEmail => (ErrorKind::InvalidEmail, None),
+            rswot::Error::Abuse => (ErrorKind::Abuse, None),
+            rswot::Error::Stop => (ErrorKind::Stop, None),
+            rswot::Error::NotAcademic => (ErrorKind::NotAcademic, None),
+            rswot::Error::NoMailServer => (ErrorKind::NoMailServer, None),
+            rswot::Error::FreeMailProvider => (ErrorKind::FreeMailProvider, None),
+            rswot::Error::DisposableProvider => (ErrorKind::DisposableProvider, None),
+            rswot::Error::AbusedAcademicDomain => (ErrorKind::AbusedAcademicDomain, None),
+            rswot::Error::SuspectedImpersonation { target } => {
+                (ErrorKind::SuspectedImpersonation, Some(target))
+            }
+            rswot::Error::Deceptive { target } => (ErrorKind::Deceptive, Some(target)),
+            rswot::Error::MixedScript => (ErrorKind::MixedScript, None),
+            rswot::Error::Confusable { target } => (ErrorKind::Confusable, Some(target)),
+            rswot::Error::RoleAccount => (ErrorKind::RoleAccount, None),
+            rswot::Error::Cohort { .. } => (ErrorKind::Cohort, None),
+        };
+        ValidationError {
+            kind,
+            message,
+            target,
+        }
+    }
+}
+
+/// Email address
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Email(rswot::Email);
+
+#[wasm_bindgen]
+impl Email {
+    /// Parse and normalize an email address
+    pub fn parse(email: &str) -> Result<Email, ValidationError> {
+        Ok(Email(rswot::Email::parse(email)?))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn user(&self) -> String {
+        self.0.user().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn domain(&self) -> String {
+        self.0.domain().to_string()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Result of validation
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Validation(rswot::Validation);
+
+#[wasm_bindgen]
+impl Validation {
+    /// Email address that was validated
+    #[wasm_bindgen(getter)]
+    pub fn email(&self) -> Email {
+        Email(self.0.email.clone())
+    }
+
+    /// Top-level domain of email
+    #[wasm_bindgen(getter)]
+    pub fn tld(&self) -> String {
+        self.0.tld.to_string()
+    }
+
+    /// Names of the institution that the email is from
+    #[wasm_bindgen(getter = institutionNames)]
+    pub fn institution_names(&self) -> Option<Vec<String>> {
+        self.0.institution_names.as_ref().map(to_strings)
+    }
+
+    /// Affiliation hinted by the labels of the domain: `student`, `staff`,
+    /// `alumni` or `unknown`
+    #[wasm_bindgen(getter)]
+    pub fn affiliation(&self) -> String {
+        self.0.affiliation.as_str().to_string()
+    }
+
+    /// Whether the email is a role account like `admin@` rather than an individual
+    #[wasm_bindgen(getter = roleAccount)]
+    pub fn role_account(&self) -> bool {
+        self.0.role_account
+    }
+}
+
+/// Validate an email address, throws a `ValidationError` if it is rejected
+#[wasm_bindgen]
+pub fn validate(email: &str) -> Result<Validation, ValidationError> {
+    Ok(Validation(rswot::validate(email)?))
+}
+
+/// Likely corrections of an email whose domain is not academic, to prompt
+/// "Did you mean ...?"
+#[wasm_bindgen]
+pub fn suggest(email: &str) -> Vec<Email> {
+    rswot::suggest(email).into_iter().map(Email).collect()
+}
+
+/// Look up the names of the institution that a domain belongs to
+#[wasm_bindgen(js_name = institutionNames)]
+pub fn institution_names(domain: &str) -> Option<Vec<String>> {
+    rswot::institution_names(domain).map(|names| to_strings(names.iter().copied()))
+}
+
+fn to_strings<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    names.into_iter().map(|name| name.to_string()).collect()
+}
@@ -0,0 +1,61 @@
+//! Run with `wasm-pack test --node bindings/wasm`
+#![cfg(target_arch = "wasm32")]
+
+use rswot_wasm::{Email, ErrorKind, institution_names, suggest, validate};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_validate() {
+    let validation = validate("Lreilly@Stanford.edu").unwrap();
+    assert_eq!(validation.email().to_js_string(), "lreilly@stanford.edu");
+    assert_eq!(validation.tld(), "edu");
+    assert_eq!(
+        validation.institution_names(),
+        Some(vec!["Stanford University".to_string()])
+    );
+    assert_eq!(validation.affiliation(), "unknown");
+    assert!(!validation.role_account());
+    let validation = validate("lee@students.uonbi.ac.ke").unwrap();
+    assert_eq!(validation.affiliation(), "student");
+    assert!(validate("admin@stanford.edu").unwrap().role_account());
+}
+
+#[wasm_bindgen_test]
+fn test_validate_error() {
+    assert_eq!(
+        validate("lee@gmail.com").unwrap_err().kind(),
+        ErrorKind::FreeMailProvider
+    );
+    assert_eq!(
+        validate("lee@leerilly.net").unwrap_err().kind(),
+        ErrorKind::NotAcademic
+    );
+    assert_eq!(validate("lee").unwrap_err().kind(), ErrorKind::InvalidEmail);
+
+    let error = validate("lee@strath.ac.uk.com").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Deceptive);
+    assert_eq!(error.target().as_deref(), Some("strath.ac.uk"));
+}
+
+#[wasm_bindgen_test]
+fn test_email_parse() {
+    let email = Email::parse(" Orhanbalci@ku.edu.tr").unwrap();
+    assert_eq!(email.user(), "orhanbalci");
+    assert_eq!(email.domain(), "ku.edu.tr");
+}
+
+#[wasm_bindgen_test]
+fn test_suggest() {
+    let suggestions = suggest("lreilly@stanford.eud");
+    assert_eq!(suggestions[0].to_js_string(), "lreilly@stanford.edu");
+    assert!(suggest("lreilly@stanford.edu").is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_institution_names() {
+    assert_eq!(
+        institution_names("cs.strath.ac.uk").unwrap()[0],
+        "University of Strathclyde"
+    );
+    assert_eq!(institution_names("leerilly.net"), None);
+}
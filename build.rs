@@ -1,4 +1,6 @@
-use std::collections::HashSet;
+#[cfg(feature = "fst")]
+use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeSet, HashSet};
 use std::fs::{self, File};
 use std::io::{BufRead, BufWriter, Write};
 use std::path::Path;
@@ -8,95 +10,463 @@ use phf_codegen::{Map, Set};
 
 const SKIP_FILES: &[&str] = &["tlds.txt", "abused.txt", "stoplist.txt"];
 
-fn create_institutions(domains_dir: &Path) {
-    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("institutions.rs");
-    let mut file = BufWriter::new(File::create(&out_path).unwrap());
+/// Country code and regional top-level domains selected by each `tlds-*` feature.
+///
+/// Top-level domains missing from this list are selected by `tlds-generic`.
+const REGIONS: &[(&str, &[&str])] = &[
+    (
+        "africa",
+        &[
+            "ao", "bf", "bi", "bj", "bw", "cd", "ci", "cm", "cv", "dj", "dz", "eg", "er", "et",
+            "gh", "gm", "ke", "km", "lr", "ls", "ly", "ma", "mg", "ml", "mr", "mu", "mw", "mz",
+            "na", "ne", "ng", "re", "rw", "sc", "sd", "sh", "sn", "so", "sz", "td", "tg", "tn",
+            "tz", "ug", "za", "zm", "zw",
+        ],
+    ),
+    (
+        "americas",
+        &[
+            "ag", "ai", "ar", "bb", "bm", "bo", "br", "bs", "bz", "ca", "cl", "co", "cr", "cu",
+            "cw", "do", "ec", "gd", "gl", "gt", "gy", "hn", "ht", "jm", "kn", "ky", "lc", "mx",
+            "ni", "pa", "pe", "pr", "py", "quebec", "rio", "sr", "sv", "tt", "us", "uy", "vc",
+            "ve", "vg",
+        ],
+    ),
+    (
+        "asia",
+        &[
+            "ae", "af", "am", "asia", "az", "bd", "bh", "bn", "bt", "cn", "ge", "hk", "id", "il",
+            "in", "iq", "ir", "jo", "jp", "kg", "kh", "kr", "krd", "kw", "kz", "la", "lb", "lk",
+            "mm", "mn", "mo", "mv", "my", "np", "om", "ph", "pk", "ps", "qa", "sa", "sg", "sy",
+            "th", "tj", "tm", "tr", "tw", "uz", "vn", "ye",
+        ],
+    ),
+    (
+        "europe",
+        &[
+            "ad",
+            "al",
+            "at",
+            "ax",
+            "ba",
+            "barcelona",
+            "bayern",
+            "be",
+            "berlin",
+            "bg",
+            "by",
+            "bzh",
+            "cat",
+            "ch",
+            "corsica",
+            "cy",
+            "cymru",
+            "cz",
+            "de",
+            "dk",
+            "ee",
+            "es",
+            "eu",
+            "eus",
+            "fi",
+            "fo",
+            "fr",
+            "gal",
+            "gg",
+            "gi",
+            "gr",
+            "hamburg",
+            "hr",
+            "hu",
+            "ie",
+            "im",
+            "is",
+            "it",
+            "je",
+            "koeln",
+            "li",
+            "london",
+            "lt",
+            "lu",
+            "lv",
+            "md",
+            "me",
+            "mk",
+            "moscow",
+            "mt",
+            "nl",
+            "no",
+            "nrw",
+            "paris",
+            "pl",
+            "pt",
+            "ro",
+            "rs",
+            "ru",
+            "scot",
+            "se",
+            "si",
+            "sk",
+            "sm",
+            "su",
+            "swiss",
+            "ua",
+            "uk",
+            "wales",
+            "yu",
+        ],
+    ),
+    (
+        "oceania",
+        &[
+            "au", "ck", "fj", "fm", "nc", "nu", "nz", "pf", "pg", "pw", "ws",
+        ],
+    ),
+];
 
-    let mut map = Map::<String>::new();
+/// Top-level domains selected by the enabled `tlds-*` features, `None` when every
+/// top-level domain is included.
+struct Selection {
+    regions: Vec<&'static str>,
+    generic: bool,
+    edu: bool,
+}
+
+impl Selection {
+    fn from_features() -> Option<Selection> {
+        let enabled =
+            |name: &str| env::var(format!("CARGO_FEATURE_TLDS_{}", name.to_uppercase())).is_ok();
+        let selection = Selection {
+            regions: REGIONS
+                .iter()
+                .map(|(region, _)| *region)
+                .filter(|region| enabled(region))
+                .collect(),
+            generic: enabled("generic"),
+            edu: enabled("edu"),
+        };
+        if selection.regions.is_empty() && !selection.generic && !selection.edu {
+            return None;
+        }
+        Some(selection)
+    }
+
+    fn contains(&self, domain: &str) -> bool {
+        let tld = domain.rsplit('.').next().unwrap_or(domain);
+        match REGIONS.iter().find(|(_, tlds)| tlds.contains(&tld)) {
+            Some((region, _)) => self.regions.contains(region),
+            None => self.generic || (self.edu && tld == "edu"),
+        }
+    }
+}
+
+/// Institution domain along with the institution names
+type Institution = (String, Vec<String>);
+
+fn read_institutions(domains_dir: &Path, with_names: bool) -> Vec<Institution> {
+    let mut institutions = Vec::new();
 
-    fn walk(dir: &Path, domains_root: &Path, map: &mut phf_codegen::Map<String>) {
+    fn walk(
+        dir: &Path,
+        domains_root: &Path,
+        with_names: bool,
+        institutions: &mut Vec<Institution>,
+    ) {
         if let Ok(rd) = fs::read_dir(dir) {
             for e in rd.flatten() {
                 let path = e.path();
                 if path.is_dir() {
-                    walk(&path, domains_root, map);
+                    walk(&path, domains_root, with_names, institutions);
                 } else if path.extension().is_some_and(|e| e == "txt") {
                     let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
                     let parent = path.parent().unwrap_or(dir);
                     if parent == domains_root && SKIP_FILES.contains(&name) {
                         continue;
                     }
+                    // Keys are plain domain names so lookups can probe suffixes of the input
                     let key = path
                         .strip_prefix(domains_root)
                         .unwrap_or(&path)
                         .with_extension("");
-                    let key = key.to_string_lossy().replace('\\', "/");
-
-                    let lines: Vec<String> = io::BufReader::new(File::open(&path).unwrap())
-                        .lines()
-                        .map_while(Result::ok)
-                        .map(|l| l.trim().to_string())
-                        .filter(|l| !l.is_empty())
-                        .collect();
-
-                    let value_lit = format!(
-                        "&[{}]",
-                        lines
-                            .iter()
-                            .map(|l| format!("{:?}", l))
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    );
-                    map.entry(key, value_lit);
+                    let key = key
+                        .components()
+                        .rev()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join(".");
+
+                    let lines: Vec<String> = if with_names {
+                        io::BufReader::new(File::open(&path).unwrap())
+                            .lines()
+                            .map_while(Result::ok)
+                            .map(|l| l.trim().to_string())
+                            .filter(|l| !l.is_empty())
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+
+                    institutions.push((key, lines));
                 }
             }
         }
     }
 
-    walk(domains_dir, domains_dir, &mut map);
-
-    writeln!(
-        &mut file,
-        "static INSTITUTIONS: phf::Map<&'static str, &'static [&'static str]> = \n{};\n",
-        map.build()
-    )
-    .unwrap();
+    walk(domains_dir, domains_dir, with_names, &mut institutions);
+    institutions
 }
 
-fn create_set(file_path: &Path, set_name: &str) {
+fn read_set(file_path: &Path, set_name: &str) -> Vec<String> {
     let lines = File::open(file_path).map(|file| io::BufReader::new(file).lines());
+    let mut entries = Vec::new();
     if let Ok(lines) = lines {
-        let set_source_path =
-            Path::new(&env::var("OUT_DIR").unwrap()).join(format!("{}.rs", set_name));
-        let mut file = BufWriter::new(File::create(&set_source_path).unwrap());
-        let mut set: Set<String> = Set::new();
         let mut duplicates = HashSet::new();
 
         for line in lines {
             if let Ok(l) = line {
                 if duplicates.insert(l.clone()) {
-                    set.entry(l);
+                    entries.push(l);
                 }
             } else {
                 println!("Error on {} set line {:?}", set_name, line);
             }
         }
+    } else {
+        println!("Can not read {} file", set_name);
+    }
+    entries
+}
+
+fn create_institutions(institutions: &[Institution]) {
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("institutions.rs");
+    let mut file = BufWriter::new(File::create(&out_path).unwrap());
+
+    let mut map = Map::<String>::new();
+    for (key, lines) in institutions {
+        let value_lit = format!(
+            "&[{}]",
+            lines
+                .iter()
+                .map(|l| format!("{:?}", l))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        map.entry(key.clone(), value_lit);
+    }
+
+    writeln!(
+        &mut file,
+        "static INSTITUTIONS: phf::Map<&'static str, &'static [&'static str]> = \n{};\n",
+        map.build()
+    )
+    .unwrap();
+}
+
+fn create_set(entries: &[String], set_name: &str) {
+    let set_source_path = Path::new(&env::var("OUT_DIR").unwrap()).join(format!("{}.rs", set_name));
+    let mut file = BufWriter::new(File::create(&set_source_path).unwrap());
+    let mut set: Set<String> = Set::new();
+
+    for entry in entries {
+        set.entry(entry.clone());
+    }
+
+    writeln!(
+        &mut file,
+        "static {}: phf::Set<&'static str> = \n{};\n",
+        set_name.to_uppercase(),
+        set.build()
+    )
+    .unwrap();
+}
+
+/// Reverse the labels of a domain (`stanford.edu` becomes `edu.stanford`)
+/// so domains sharing a suffix share a prefix in the FST.
+#[cfg(feature = "fst")]
+fn reversed(domain: &str) -> String {
+    domain.rsplit('.').collect::<Vec<_>>().join(".")
+}
+
+/// Encode the datasets as FSTs keyed by reversed domains.
+///
+/// Institution values pack the offset and length of the names in a deduplicated
+/// `NAMES` table along with the index of the top-level label in `TOP_LABELS`,
+/// so the lookups can hand out `'static` data.
+#[cfg(feature = "fst")]
+fn create_fst(institutions: &[Institution], sets: &[(&str, &[String])]) {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir);
+
+    let top_labels: BTreeSet<&str> = institutions
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .chain(
+            sets.iter()
+                .flat_map(|(_, entries)| entries.iter().map(String::as_str)),
+        )
+        .map(|domain| domain.rsplit('.').next().unwrap_or(domain))
+        .collect();
+    assert!(
+        top_labels.len() <= 1 << 16,
+        "top-level labels don't fit in 16 bits of FST values"
+    );
+    let top_label_index = |domain: &str| {
+        let label = domain.rsplit('.').next().unwrap_or(domain);
+        top_labels.iter().position(|l| *l == label).unwrap() as u64
+    };
+
+    let mut names: Vec<&str> = Vec::new();
+    let mut offsets: HashMap<&[String], usize> = HashMap::new();
+    let mut entries = BTreeMap::new();
+    for (key, lines) in institutions {
+        let offset = *offsets.entry(lines.as_slice()).or_insert_with(|| {
+            names.extend(lines.iter().map(String::as_str));
+            names.len() - lines.len()
+        });
+        // Fields must not overlap in the packed value
+        assert!(
+            offset < 1 << 32,
+            "names offset of {} doesn't fit in 32 bits",
+            key
+        );
+        assert!(
+            lines.len() < 1 << 16,
+            "names of {} don't fit in 16 bits",
+            key
+        );
+        let value = ((offset as u64) << 32) | ((lines.len() as u64) << 16) | top_label_index(key);
+        entries.insert(reversed(key), value);
+    }
+    fs::write(
+        out_dir.join("institutions.fst"),
+        fst::Map::from_iter(entries)
+            .unwrap()
+            .into_fst()
+            .into_inner(),
+    )
+    .unwrap();
+
+    for (set_name, set_entries) in sets {
+        let entries: BTreeMap<String, u64> = set_entries
+            .iter()
+            .map(|entry| (reversed(entry), top_label_index(entry)))
+            .collect();
+        fs::write(
+            out_dir.join(format!("{}.fst", set_name)),
+            fst::Map::from_iter(entries)
+                .unwrap()
+                .into_fst()
+                .into_inner(),
+        )
+        .unwrap();
+    }
+
+    let mut file = BufWriter::new(File::create(out_dir.join("fst.rs")).unwrap());
+    writeln!(
+        &mut file,
+        "static TOP_LABELS: &[&str] = &{:?};\n\nstatic NAMES: &[&str] = &{:?};\n",
+        top_labels.iter().collect::<Vec<_>>(),
+        names
+    )
+    .unwrap();
+}
+
+fn create_dataset(institutions: &[Institution], sets: &[(&str, &[String])]) {
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("dataset.rs");
+    let mut file = BufWriter::new(File::create(&out_path).unwrap());
+
+    let top_level_domains: BTreeSet<&str> = institutions
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .chain(
+            sets.iter()
+                .filter(|(set_name, _)| *set_name == "tlds")
+                .flat_map(|(_, entries)| entries.iter().map(String::as_str)),
+        )
+        .map(|domain| domain.rsplit('.').next().unwrap_or(domain))
+        .collect();
+
+    writeln!(
+        &mut file,
+        "static TOP_LEVEL_DOMAINS: &[&str] = &{:?};\n",
+        top_level_domains.iter().collect::<Vec<_>>()
+    )
+    .unwrap();
 
+    writeln!(
+        &mut file,
+        "const INSTITUTIONS_COUNT: usize = {};\n",
+        institutions.len()
+    )
+    .unwrap();
+    writeln!(
+        &mut file,
+        "const INSTITUTIONS_HASH: u64 = {};\n",
+        hash_entries(institutions.iter().map(|(key, _)| key.as_str()))
+    )
+    .unwrap();
+    for (set_name, entries) in sets {
         writeln!(
             &mut file,
-            "static {}: phf::Set<&'static str> = \n{};\n",
+            "const {}_COUNT: usize = {};\n",
             set_name.to_uppercase(),
-            set.build()
+            entries.len()
+        )
+        .unwrap();
+        writeln!(
+            &mut file,
+            "const {}_HASH: u64 = {};\n",
+            set_name.to_uppercase(),
+            hash_entries(entries.iter().map(String::as_str))
         )
         .unwrap();
-    } else {
-        println!("Can not read {} file", set_name);
     }
 }
 
+/// Hash of the entries of a list whatever their order, the sum of their
+/// FNV-1a hashes. `src/dataset.rs` hashes loaded datasets the same way.
+fn hash_entries<'a>(entries: impl Iterator<Item = &'a str>) -> u64 {
+    entries
+        .map(|entry| {
+            entry.bytes().fold(0xcbf29ce484222325, |hash: u64, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+            })
+        })
+        .fold(0, u64::wrapping_add)
+}
+
 fn main() {
-    create_set(&Path::new("domains").join("tlds.txt"), "tlds");
-    create_set(&Path::new("domains").join("abused.txt"), "abused");
-    create_set(&Path::new("domains").join("stoplist.txt"), "stoplist");
-    create_institutions(Path::new("domains"));
+    let domains_dir = Path::new("domains");
+    let lists_dir = Path::new("lists");
+    let with_names = env::var("CARGO_FEATURE_NAMES").is_ok();
+    let selection = Selection::from_features();
+    let selected = |domain: &str| selection.as_ref().is_none_or(|s| s.contains(domain));
+
+    let mut tlds = read_set(&domains_dir.join("tlds.txt"), "tlds");
+    let abused = read_set(&domains_dir.join("abused.txt"), "abused");
+    let stoplist = read_set(&domains_dir.join("stoplist.txt"), "stoplist");
+    let freemail = read_set(&lists_dir.join("freemail.txt"), "freemail");
+    let disposable = read_set(&lists_dir.join("disposable.txt"), "disposable");
+    let mut institutions = read_institutions(domains_dir, with_names);
+
+    // The other lists are kept whole, they only ever reject emails
+    tlds.retain(|tld| selected(tld));
+    institutions.retain(|(key, _)| selected(key));
+
+    let sets: &[(&str, &[String])] = &[
+        ("tlds", &tlds),
+        ("abused", &abused),
+        ("stoplist", &stoplist),
+        ("freemail", &freemail),
+        ("disposable", &disposable),
+    ];
+    create_dataset(&institutions, sets);
+
+    for (set_name, entries) in sets {
+        create_set(entries, set_name);
+    }
+    create_institutions(&institutions);
+
+    #[cfg(feature = "fst")]
+    create_fst(&institutions, sets);
 }
@@ -0,0 +1,220 @@
+//! HTTP service for [rswot](https://docs.rs/rswot).
+//!
+//! | Method | Path                   | Description                                      |
+//! |--------|------------------------|--------------------------------------------------|
+//! | POST   | `/validate`            | Validate `{"email": ..}` or `{"emails": [..]}`   |
+//! | GET    | `/domains/{domain}`    | Validate a domain                                |
+//! | GET    | `/institutions?q=`     | Search institutions by domain or name            |
+//! | GET    | `/dataset`             | Version and counts of the dataset                |
+//! | GET    | `/metrics`             | Validation metrics in the Prometheus text format |
+//! | GET    | `/healthz`, `/readyz`  | Liveness and readiness                           |
+
+use std::future::Future;
+use std::io;
+use std::sync::{Arc, LazyLock};
+
+use axum::extract::{Path, Query};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rswot::dataset::Dataset;
+use rswot::{Metrics, Validator};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+
+/// Maximum number of emails in a batch validation
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+
+static METRICS: LazyLock<Arc<Metrics>> = LazyLock::new(|| Arc::new(Metrics::new()));
+
+static VALIDATOR: LazyLock<Validator> =
+    LazyLock::new(|| Validator::new(Dataset::embedded()).with_observer(METRICS.clone()));
+
+/// Routes of the service
+pub fn router() -> Router {
+    Router::new()
+        .route("/validate", post(validate))
+        .route("/domains/{domain}", get(domain))
+        .route("/institutions", get(institutions))
+        .route("/dataset", get(dataset))
+        .route("/metrics", get(metrics))
+        .route("/healthz", get(health))
+        .route("/readyz", get(ready))
+}
+
+/// Serve the routes on `listener` until `shutdown` completes, then wait for
+/// in-flight requests to finish.
+pub async fn serve<F>(listener: TcpListener, shutdown: F) -> io::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    axum::serve(listener, router())
+        .with_graceful_shutdown(shutdown)
+        .await
+}
+
+/// Outcome of a validation
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum Outcome<T> {
+    Valid {
+        validation: T,
+    },
+    Invalid {
+        error: rswot::Error,
+        message: String,
+    },
+}
+
+impl<T> From<rswot::Result<T>> for Outcome<T> {
+    fn from(result: rswot::Result<T>) -> Outcome<T> {
+        match result {
+            Ok(validation) => Outcome::Valid { validation },
+            Err(error) => Outcome::Invalid {
+                message: error.to_string(),
+                error,
+            },
+        }
+    }
+}
+
+/// Error of a request that could not be processed
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+}
+
+impl ApiError {
+    fn bad_request(error: impl Into<String>) -> Response {
+        let error = ApiError {
+            error: error.into(),
+        };
+        (StatusCode::BAD_REQUEST, Json(error)).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ValidateRequest {
+    Single { email: String },
+    Batch { emails: Vec<String> },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ValidateResponse {
+    Single(Outcome<rswot::Validation>),
+    Batch {
+        results: Vec<Outcome<rswot::Validation>>,
+    },
+}
+
+async fn validate(Json(request): Json<ValidateRequest>) -> Response {
+    match request {
+        ValidateRequest::Single { email } => {
+            Json(ValidateResponse::Single(VALIDATOR.validate(email).into())).into_response()
+        }
+        ValidateRequest::Batch { emails } if emails.len() > MAX_BATCH_SIZE => {
+            ApiError::bad_request(format!("At most {} emails per batch", MAX_BATCH_SIZE))
+        }
+        ValidateRequest::Batch { emails } => {
+            let results = emails
+                .into_iter()
+                .map(|email| VALIDATOR.validate(email).into())
+                .collect();
+            Json(ValidateResponse::Batch { results }).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DomainResponse {
+    domain: String,
+    #[serde(flatten)]
+    outcome: Outcome<rswot::DomainValidation>,
+}
+
+async fn domain(Path(domain): Path<String>) -> Json<DomainResponse> {
+    let outcome = VALIDATOR.validate_domain(&domain).into();
+    Json(DomainResponse { domain, outcome })
+}
+
+#[derive(Debug, Deserialize)]
+struct InstitutionsQuery {
+    q: String,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct InstitutionsResponse {
+    institutions: Vec<rswot::dataset::Institution>,
+}
+
+async fn institutions(Query(query): Query<InstitutionsQuery>) -> Response {
+    let needle = query.q.trim().to_lowercase();
+    if needle.is_empty() {
+        return ApiError::bad_request("Query `q` must not be empty");
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .min(MAX_SEARCH_LIMIT);
+
+    let mut institutions: Vec<_> = rswot::dataset::institutions()
+        .filter(|institution| {
+            institution.domain.contains(&needle)
+                || institution
+                    .names
+                    .iter()
+                    .any(|name| name.to_lowercase().contains(&needle))
+        })
+        .collect();
+    institutions.sort_by(|a, b| a.domain.cmp(&b.domain));
+    institutions.truncate(limit);
+
+    Json(InstitutionsResponse { institutions }).into_response()
+}
+
+#[derive(Debug, Serialize)]
+struct DatasetResponse {
+    version: &'static str,
+    has_institution_names: bool,
+    counts: rswot::dataset::Counts,
+    top_level_domains: &'static [&'static str],
+}
+
+async fn dataset() -> Json<DatasetResponse> {
+    Json(DatasetResponse {
+        version: rswot::dataset::VERSION,
+        has_institution_names: rswot::dataset::HAS_INSTITUTION_NAMES,
+        counts: rswot::dataset::counts(),
+        top_level_domains: rswot::dataset::top_level_domains(),
+    })
+}
+
+async fn metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        METRICS.render(),
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct HealthResponse {
+    status: &'static str,
+}
+
+async fn health() -> Json<HealthResponse> {
+    Json(HealthResponse { status: "ok" })
+}
+
+async fn ready() -> Json<HealthResponse> {
+    // Force the lazily initialized parts of the dataset before taking traffic,
+    // a domain is not counted in the metrics
+    let _ = VALIDATOR.validate_domain("stanford.edu");
+    Json(HealthResponse { status: "ok" })
+}
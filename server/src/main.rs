@@ -0,0 +1,65 @@
+use std::env;
+use std::process::ExitCode;
+
+use tokio::net::TcpListener;
+use tokio::signal;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+const USAGE: &str = "Usage: rswot-server [--addr <host:port>]
+
+Options:
+  --addr <host:port>  Address to listen on, defaults to $RSWOT_ADDR or 127.0.0.1:8080";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let mut addr = env::var("RSWOT_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_string());
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--addr", Some(value)) => addr = value,
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Unable to listen on {}: {}", addr, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("Listening on {}", addr);
+
+    if let Err(error) = rswot_server::serve(listener, shutdown_signal()).await {
+        eprintln!("Server error: {}", error);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Complete on Ctrl+C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c().await.expect("Unable to listen for Ctrl+C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Unable to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    eprintln!("Shutting down");
+}
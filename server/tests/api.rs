@@ -0,0 +1,223 @@
+use std::net::SocketAddr;
+
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+struct Server {
+    addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    handle: JoinHandle<std::io::Result<()>>,
+}
+
+impl Server {
+    async fn start() -> Server {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown, receiver) = oneshot::channel();
+        let handle = tokio::spawn(rswot_server::serve(listener, async {
+            let _ = receiver.await;
+        }));
+        Server {
+            addr,
+            shutdown,
+            handle,
+        }
+    }
+
+    async fn stop(self) {
+        self.shutdown.send(()).unwrap();
+        self.handle.await.unwrap().unwrap();
+    }
+
+    /// Send a request and return the status code along with the JSON body
+    async fn request(&self, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+        let (status, body) = self.request_text(method, path, body).await;
+        (status, serde_json::from_str(&body).unwrap_or(Value::Null))
+    }
+
+    /// Send a request and return the status code along with the raw body
+    async fn request_text(&self, method: &str, path: &str, body: Option<Value>) -> (u16, String) {
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        let request = format!(
+            "{method} {path} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            self.addr,
+            body.len()
+        );
+
+        let mut stream = TcpStream::connect(self.addr).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_validate() {
+    let server = Server::start().await;
+
+    let (status, body) = server
+        .request(
+            "POST",
+            "/validate",
+            Some(json!({"email": "Lreilly@Stanford.edu"})),
+        )
+        .await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        json!({
+            "status": "valid",
+            "validation": {
+                "email": "lreilly@stanford.edu",
+                "tld": "edu",
+                "institution_names": ["Stanford University"],
+                "affiliation": "unknown",
+                "role_account": false,
+                "dataset_hash": rswot::dataset::hash().to_string(),
+            },
+        })
+    );
+
+    let (status, body) = server
+        .request("POST", "/validate", Some(json!({"email": "lee@gmail.com"})))
+        .await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        json!({
+            "status": "invalid",
+            "error": "free_mail_provider",
+            "message": "Email is from a free mail provider",
+        })
+    );
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_validate_batch() {
+    let server = Server::start().await;
+
+    let (status, body) = server
+        .request(
+            "POST",
+            "/validate",
+            Some(json!({"emails": ["lee@ugr.es", "john@america.edu", "the"]})),
+        )
+        .await;
+    assert_eq!(status, 200);
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["status"], "valid");
+    assert_eq!(results[1]["error"], "stop");
+    assert_eq!(results[2]["error"], "invalid_email");
+
+    let emails = vec!["lee@ugr.es"; rswot_server::MAX_BATCH_SIZE + 1];
+    let (status, _) = server
+        .request("POST", "/validate", Some(json!({ "emails": emails })))
+        .await;
+    assert_eq!(status, 400);
+
+    let (status, _) = server
+        .request("POST", "/validate", Some(json!({"mail": "lee@ugr.es"})))
+        .await;
+    assert_eq!(status, 422);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_domain() {
+    let server = Server::start().await;
+
+    let (status, body) = server.request("GET", "/domains/uct.ac.za", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        json!({
+            "domain": "uct.ac.za",
+            "status": "valid",
+            "validation": {
+                "tld": "za",
+                "institution_names": null,
+                "affiliation": "unknown",
+                "dataset_hash": rswot::dataset::hash().to_string(),
+            },
+        })
+    );
+
+    let (_, body) = server.request("GET", "/domains/leerilly.net", None).await;
+    assert_eq!(body["error"], "not_academic");
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_institutions() {
+    let server = Server::start().await;
+
+    let (status, body) = server
+        .request("GET", "/institutions?q=stanford%20university&limit=5", None)
+        .await;
+    assert_eq!(status, 200);
+    let institutions = body["institutions"].as_array().unwrap();
+    assert!(!institutions.is_empty() && institutions.len() <= 5);
+    assert!(
+        institutions
+            .iter()
+            .any(|institution| institution["domain"] == "stanford.edu")
+    );
+
+    let (status, _) = server.request("GET", "/institutions?q=", None).await;
+    assert_eq!(status, 400);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_dataset_and_health() {
+    let server = Server::start().await;
+
+    let (status, body) = server.request("GET", "/dataset", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["version"], rswot::dataset::VERSION);
+    assert!(body["counts"]["institutions"].as_u64().unwrap() > 0);
+
+    for path in ["/healthz", "/readyz"] {
+        let (status, body) = server.request("GET", path, None).await;
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({"status": "ok"}));
+    }
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let server = Server::start().await;
+
+    let (status, _) = server
+        .request(
+            "POST",
+            "/validate",
+            Some(json!({"email": "lee@mailinator.com"})),
+        )
+        .await;
+    assert_eq!(status, 200);
+
+    // Other tests validate emails concurrently, so counts are not checked
+    let (status, body) = server.request_text("GET", "/metrics", None).await;
+    assert_eq!(status, 200);
+    assert!(body.contains("# TYPE rswot_validations_total counter\n"));
+    assert!(body.contains("rswot_validations_total{outcome=\"disposable_provider\"} "));
+    assert!(body.contains("rswot_validations_by_match_source_total{match_source=\"disposable\"} "));
+
+    server.stop().await;
+}
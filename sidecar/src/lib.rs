@@ -0,0 +1,280 @@
+//! JSON lines sidecar for [rswot](https://docs.rs/rswot).
+//!
+//! Each request is a JSON object on its own line, answered by a JSON object on
+//! its own line carrying the same `id`:
+//!
+//! ```text
+//! {"id": 1, "email": "lreilly@stanford.edu"}
+//! {"id": 1, "status": "valid", "validation": {"email": "lreilly@stanford.edu", "tld": "edu", "institution_names": ["Stanford University"]}}
+//! {"id": 2, "command": "reload"}
+//! {"id": 2, "status": "reloaded", "counts": {"institutions": 26845, "academic_suffixes": 94, "stoplist": 1306, "abused": 1858}}
+//! ```
+
+use std::io::{self, BufRead, Read, Write};
+use std::path::PathBuf;
+use std::str;
+
+use rswot::Validator;
+use rswot::dataset::{Counts, Dataset};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Longest request line, in bytes without the newline, longer ones are answered
+/// with an error and skipped
+pub const MAX_LINE_LEN: usize = 64 * 1024;
+
+/// Sidecar state, shared by every client
+#[derive(Debug)]
+pub struct Sidecar {
+    validator: Validator,
+    domains_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    email: Option<String>,
+    command: Option<Command>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Command {
+    Reload,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    id: Value,
+    #[serde(flatten)]
+    body: Body,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum Body {
+    Valid {
+        validation: rswot::Validation,
+    },
+    Invalid {
+        error: rswot::Error,
+        message: String,
+    },
+    Reloaded {
+        counts: Counts,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl Sidecar {
+    /// Sidecar validating against the dataset loaded from `domains_dir`, or the
+    /// embedded dataset if there is none
+    pub fn new(domains_dir: Option<PathBuf>) -> io::Result<Sidecar> {
+        let dataset = match &domains_dir {
+            Some(dir) => Dataset::load(dir)?,
+            None => Dataset::embedded(),
+        };
+        Ok(Sidecar {
+            validator: Validator::new(dataset),
+            domains_dir,
+        })
+    }
+
+    /// Cache the decisions of up to `capacity` domains, dropped on reload
+    pub fn with_cache(self, capacity: usize) -> Sidecar {
+        Sidecar {
+            validator: self.validator.with_cache(capacity),
+            ..self
+        }
+    }
+
+    /// Validator answering the requests
+    pub fn validator(&self) -> &Validator {
+        &self.validator
+    }
+
+    /// Answer a single request line
+    pub fn handle_line(&self, line: &str) -> String {
+        let response = match serde_json::from_str::<Request>(line) {
+            Ok(request) => Response {
+                body: self.handle(&request),
+                id: request.id,
+            },
+            Err(error) => return error_line(format!("Invalid request: {}", error)),
+        };
+        serde_json::to_string(&response).unwrap()
+    }
+
+    fn handle(&self, request: &Request) -> Body {
+        match (&request.command, &request.email) {
+            (Some(Command::Reload), _) => self.reload(),
+            (None, Some(email)) => match self.validator.validate(email.as_str()) {
+                Ok(validation) => Body::Valid { validation },
+                Err(error) => Body::Invalid {
+                    message: error.to_string(),
+                    error,
+                },
+            },
+            (None, None) => Body::Error {
+                message: "Request needs an `email` or a `command`".to_string(),
+            },
+        }
+    }
+
+    /// Load the dataset again from the configured directory and swap it in
+    /// place, requests in flight finish with the previous dataset
+    ///
+    /// Clients can't pick the directory, so they can't make the sidecar read
+    /// arbitrary paths.
+    fn reload(&self) -> Body {
+        let Some(dir) = &self.domains_dir else {
+            return Body::Error {
+                message: "No domains directory to reload from".to_string(),
+            };
+        };
+        match Dataset::load(dir) {
+            Ok(dataset) => {
+                let counts = dataset.counts();
+                self.validator.set_dataset(dataset);
+                Body::Reloaded { counts }
+            }
+            Err(error) => Body::Error {
+                message: format!("Unable to load {}: {}", dir.display(), error),
+            },
+        }
+    }
+
+    /// Answer every request line of `reader` on `writer` until the end of input
+    ///
+    /// Lines that are not UTF-8 or longer than [`MAX_LINE_LEN`] are answered
+    /// with an error, and the next lines are still served.
+    pub fn serve<R: BufRead, W: Write>(&self, mut reader: R, mut writer: W) -> io::Result<()> {
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let limit = MAX_LINE_LEN as u64 + 1;
+            if (&mut reader).take(limit).read_until(b'\n', &mut line)? == 0 {
+                return Ok(());
+            }
+            let response = if line.len() > MAX_LINE_LEN && line.last() != Some(&b'\n') {
+                skip_line(&mut reader)?;
+                error_line(format!("Request is longer than {} bytes", MAX_LINE_LEN))
+            } else {
+                match str::from_utf8(&line) {
+                    Ok(line) if line.trim().is_empty() => continue,
+                    Ok(line) => self.handle_line(line),
+                    Err(_) => error_line("Request is not valid UTF-8".to_string()),
+                }
+            };
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+        }
+    }
+}
+
+/// Response to a request that could not be read, without its `id`
+fn error_line(message: String) -> String {
+    let response = Response {
+        id: Value::Null,
+        body: Body::Error { message },
+    };
+    serde_json::to_string(&response).unwrap()
+}
+
+/// Discard the rest of the current line, without buffering it
+fn skip_line(reader: &mut impl BufRead) -> io::Result<()> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        match buffer.iter().position(|&byte| byte == b'\n') {
+            Some(end) => {
+                reader.consume(end + 1);
+                return Ok(());
+            }
+            None => {
+                let len = buffer.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+pub use self::unix::{MAX_CLIENTS, serve_unix};
+
+#[cfg(unix)]
+mod unix {
+    use std::io::{self, BufReader, Write};
+    use std::os::unix::net::UnixListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::Sidecar;
+
+    /// Clients served at once, the next ones are answered with an error and
+    /// disconnected
+    pub const MAX_CLIENTS: usize = 256;
+
+    /// Pause after a failed accept, so running out of file descriptors doesn't
+    /// spin the loop
+    const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+    /// Slot of a client being served, released when its thread ends
+    struct Slot(Arc<AtomicUsize>);
+
+    impl Slot {
+        fn take(clients: &Arc<AtomicUsize>) -> Option<Slot> {
+            clients
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                    (count < MAX_CLIENTS).then_some(count + 1)
+                })
+                .ok()
+                .map(|_| Slot(clients.clone()))
+        }
+    }
+
+    impl Drop for Slot {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Accept clients on `listener`, each one served by its own thread, up to
+    /// [`MAX_CLIENTS`] at once
+    ///
+    /// Failing to accept a client is logged and doesn't stop the server.
+    pub fn serve_unix(sidecar: Arc<Sidecar>, listener: UnixListener) -> io::Result<()> {
+        let clients = Arc::new(AtomicUsize::new(0));
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(error) => {
+                    eprintln!("Unable to accept a client: {}", error);
+                    thread::sleep(ACCEPT_BACKOFF);
+                    continue;
+                }
+            };
+            let Some(slot) = Slot::take(&clients) else {
+                let _ = writeln!(
+                    stream,
+                    r#"{{"id":null,"status":"error","message":"Too many clients"}}"#
+                );
+                continue;
+            };
+            let sidecar = sidecar.clone();
+            thread::spawn(move || {
+                let _slot = slot;
+                let reader = BufReader::new(stream.try_clone()?);
+                sidecar.serve(reader, stream)
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use rswot_sidecar::Sidecar;
+
+const USAGE: &str = "Usage: rswot-sidecar [--domains <dir>] [--socket <path>] [--cache <entries>]
+
+Reads JSON requests line by line on stdin, or on a Unix domain socket, and writes
+a JSON response line for each of them.
+
+Options:
+  --domains <dir>  Load the dataset from this directory instead of the embedded one,
+                   the reload command loads it again
+  --socket <path>  Listen on this Unix domain socket instead of stdin
+  --cache <entries>
+                   Cache the decisions of this many domains, 0 disables the cache
+                   [default: 1024]";
+
+const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+fn main() -> ExitCode {
+    let mut domains_dir = None;
+    let mut socket = None;
+    let mut cache_capacity = DEFAULT_CACHE_CAPACITY;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--domains", Some(value)) => domains_dir = Some(PathBuf::from(value)),
+            ("--socket", Some(value)) => socket = Some(PathBuf::from(value)),
+            ("--cache", Some(value)) => match value.parse() {
+                Ok(value) => cache_capacity = value,
+                Err(_) => return usage(),
+            },
+            _ => return usage(),
+        }
+    }
+
+    let sidecar = match Sidecar::new(domains_dir) {
+        Ok(sidecar) => sidecar.with_cache(cache_capacity),
+        Err(error) => {
+            eprintln!("Unable to load the dataset: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match socket {
+        Some(path) => serve_socket(sidecar, path),
+        None => sidecar.serve(io::stdin().lock(), io::stdout().lock()),
+    };
+    if let Err(error) = result {
+        eprintln!("Sidecar error: {}", error);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("{}", USAGE);
+    ExitCode::FAILURE
+}
+
+#[cfg(unix)]
+fn serve_socket(sidecar: Sidecar, path: PathBuf) -> io::Result<()> {
+    use std::fs;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+    use std::sync::Arc;
+
+    // Remove the socket left behind by a previous run
+    if fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    eprintln!("Listening on {}", path.display());
+    rswot_sidecar::serve_unix(Arc::new(sidecar), listener)
+}
+
+#[cfg(not(unix))]
+fn serve_socket(_sidecar: Sidecar, _path: PathBuf) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Unix domain sockets are not supported on this platform",
+    ))
+}
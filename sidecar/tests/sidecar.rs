@@ -0,0 +1,209 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use rswot_sidecar::{MAX_LINE_LEN, Sidecar};
+use serde_json::{Value, json};
+
+/// Minimal dataset with a single institution
+fn write_domains(name: &str, institution: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rswot-sidecar-{}-{}", process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("edu")).unwrap();
+    fs::write(dir.join("tlds.txt"), "ac.zz\n").unwrap();
+    fs::write(dir.join("stoplist.txt"), "america.edu\n").unwrap();
+    fs::write(dir.join("abused.txt"), "gmail.com\n").unwrap();
+    fs::write(
+        dir.join("edu").join(format!("{}.txt", institution)),
+        "Example University\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn request(sidecar: &Sidecar, request: Value) -> Value {
+    serde_json::from_str(&sidecar.handle_line(&request.to_string())).unwrap()
+}
+
+#[test]
+fn test_validate() {
+    let sidecar = Sidecar::new(None).unwrap();
+
+    let response = request(&sidecar, json!({"id": 1, "email": "LReilly@Stanford.edu"}));
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["status"], "valid");
+    assert_eq!(response["validation"]["email"], "lreilly@stanford.edu");
+    assert_eq!(response["validation"]["tld"], "edu");
+
+    let response = request(&sidecar, json!({"id": "a", "email": "lee@gmail.com"}));
+    assert_eq!(response["id"], "a");
+    assert_eq!(response["status"], "invalid");
+    assert_eq!(response["error"], "free_mail_provider");
+
+    let response: Value = serde_json::from_str(&sidecar.handle_line("not json")).unwrap();
+    assert_eq!(response["id"], Value::Null);
+    assert_eq!(response["status"], "error");
+
+    let response = request(&sidecar, json!({"id": 2}));
+    assert_eq!(response["status"], "error");
+
+    let response = request(&sidecar, json!({"id": 3, "command": "reload"}));
+    assert_eq!(response["status"], "error");
+}
+
+#[test]
+fn test_serve_lines() {
+    let sidecar = Sidecar::new(None).unwrap();
+    let input =
+        "{\"id\": 1, \"email\": \"lee@ugr.es\"}\n\n{\"id\": 2, \"email\": \"lee@leerilly.net\"}\n";
+    let mut output = Vec::new();
+    sidecar.serve(Cursor::new(input), &mut output).unwrap();
+
+    let responses: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["status"], "valid");
+    assert_eq!(responses[1]["error"], "not_academic");
+}
+
+#[test]
+fn test_serve_bad_lines() {
+    let sidecar = Sidecar::new(None).unwrap();
+    let mut input = b"{\"id\": 1, \"email\": \"\xff@stanford.edu\"}\n".to_vec();
+    input.extend(vec![b'a'; MAX_LINE_LEN + 1]);
+    input.extend(b"\n{\"id\": 2, \"email\": \"lee@ugr.es\"}\n");
+    // Without a newline at the end of input
+    input.extend(vec![b'a'; MAX_LINE_LEN * 3]);
+    let mut output = Vec::new();
+    sidecar.serve(Cursor::new(input), &mut output).unwrap();
+
+    let responses: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 4, "{:?}", responses);
+    assert_eq!(responses[0]["status"], "error");
+    assert_eq!(responses[0]["message"], "Request is not valid UTF-8");
+    assert_eq!(responses[1]["status"], "error");
+    assert_eq!(
+        responses[1]["message"],
+        format!("Request is longer than {} bytes", MAX_LINE_LEN)
+    );
+    assert_eq!(responses[2]["id"], 2);
+    assert_eq!(responses[2]["status"], "valid");
+    assert_eq!(responses[3]["status"], "error");
+
+    // Lines of the longest length are served
+    let email = format!("{}@ugr.es", "a".repeat(40));
+    let line = format!("{{\"id\": 3, \"email\": \"{}\"}}", email);
+    let line = format!("{}{}\n", " ".repeat(MAX_LINE_LEN - line.len()), line);
+    let mut output = Vec::new();
+    sidecar.serve(Cursor::new(line), &mut output).unwrap();
+    let response: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(response["id"], 3);
+}
+
+#[test]
+fn test_reload() {
+    let dir = write_domains("reload", "first");
+    let other = write_domains("reload-other", "other");
+    let sidecar = Sidecar::new(Some(dir.clone())).unwrap().with_cache(16);
+
+    let validate =
+        |email: &str| request(&sidecar, json!({"id": 0, "email": email}))["status"].clone();
+    assert_eq!(validate("lee@first.edu"), "valid");
+    assert_eq!(validate("lee@stanford.edu"), "invalid");
+
+    fs::rename(
+        dir.join("edu").join("first.txt"),
+        dir.join("edu").join("second.txt"),
+    )
+    .unwrap();
+    let response = request(&sidecar, json!({"id": 1, "command": "reload"}));
+    assert_eq!(response["status"], "reloaded");
+    assert_eq!(response["counts"]["institutions"], 1);
+    assert_eq!(validate("lee@first.edu"), "invalid");
+    assert_eq!(validate("lee@second.edu"), "valid");
+
+    // Clients can't reload from another directory
+    let response = request(
+        &sidecar,
+        json!({"id": 2, "command": "reload", "domains": other}),
+    );
+    assert_eq!(response["status"], "reloaded");
+    assert_eq!(validate("lee@other.edu"), "invalid");
+    assert_eq!(validate("lee@second.edu"), "valid");
+
+    // The previous dataset is kept if loading fails
+    fs::remove_file(dir.join("tlds.txt")).unwrap();
+    let response = request(&sidecar, json!({"id": 3, "command": "reload"}));
+    assert_eq!(response["status"], "error");
+    assert_eq!(validate("lee@second.edu"), "valid");
+
+    for dir in [dir, other] {
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unix_socket() {
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::Arc;
+    use std::thread;
+
+    fn call(stream: &mut UnixStream, reader: &mut impl BufRead, request: Value) -> Value {
+        writeln!(stream, "{}", request).unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn connect(path: &Path) -> (UnixStream, BufReader<UnixStream>) {
+        let stream = UnixStream::connect(path).unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        (stream, reader)
+    }
+
+    let domains = write_domains("socket", "first");
+    let path = domains.join("sidecar.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    let sidecar = Arc::new(Sidecar::new(Some(domains.clone())).unwrap());
+    thread::spawn(move || rswot_sidecar::serve_unix(sidecar, listener));
+
+    let (mut first, mut first_reader) = connect(&path);
+    let (mut second, mut second_reader) = connect(&path);
+
+    let response = call(
+        &mut first,
+        &mut first_reader,
+        json!({"id": 1, "email": "lee@second.edu"}),
+    );
+    assert_eq!(response["status"], "invalid");
+
+    // Clients are served concurrently and share the reloaded dataset
+    fs::rename(
+        domains.join("edu").join("first.txt"),
+        domains.join("edu").join("second.txt"),
+    )
+    .unwrap();
+    let response = call(
+        &mut second,
+        &mut second_reader,
+        json!({"id": 2, "command": "reload"}),
+    );
+    assert_eq!(response["status"], "reloaded");
+
+    let response = call(
+        &mut first,
+        &mut first_reader,
+        json!({"id": 3, "email": "lee@second.edu"}),
+    );
+    assert_eq!(response["id"], 3);
+    assert_eq!(response["status"], "valid");
+
+    fs::remove_dir_all(domains).unwrap();
+}
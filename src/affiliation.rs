@@ -0,0 +1,277 @@
+use std::iter;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cohort;
+
+/// Affiliation of the owner of an email to its institution, as hinted by the
+/// labels of its domain
+///
+/// Labels like `students`, `estudiantes`, `staff`, `faculty` or `alumni` in
+/// several languages are recognized, and so are past graduation years like
+/// `2011` or `class2011`. It is only a hint: most institutions give the same
+/// domain to everyone.
+///
+/// Current and future graduation years give no hint, as they are used by
+/// students. See [`CohortPolicy`](crate::CohortPolicy) to restrict them.
+///
+/// # Examples
+///
+/// ```
+/// use rswot::AffiliationHint;
+///
+/// assert_eq!(
+///     AffiliationHint::from_domain("students.uonbi.ac.ke"),
+///     AffiliationHint::Student
+/// );
+/// assert_eq!(
+///     AffiliationHint::from_domain("stanford.edu"),
+///     AffiliationHint::Unknown
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AffiliationHint {
+    Student,
+    Staff,
+    Alumni,
+    #[default]
+    Unknown,
+}
+
+/// Labels of student domains
+const STUDENT_LABELS: &[&str] = &[
+    "aluno",
+    "alunos",
+    "alumno",
+    "alumnos",
+    "eleves",
+    "estudantes",
+    "estudiante",
+    "estudiantes",
+    "etu",
+    "etud",
+    "etudiant",
+    "etudiants",
+    "learner",
+    "learners",
+    "ogrenci",
+    "pupil",
+    "pupils",
+    "stu",
+    "stud",
+    "student",
+    "studenci",
+    "studenten",
+    "studenti",
+    "students",
+    "studierende",
+    "undergrad",
+];
+
+/// Labels of staff domains
+const STAFF_LABELS: &[&str] = &[
+    "docentes",
+    "dozenten",
+    "employee",
+    "employees",
+    "enseignants",
+    "faculty",
+    "funcionarios",
+    "mitarbeiter",
+    "personal",
+    "personnel",
+    "professores",
+    "profesores",
+    "staff",
+    "teachers",
+];
+
+/// Labels of alumni domains
+const ALUMNI_LABELS: &[&str] = &[
+    "absolventen",
+    "alum",
+    "alumnae",
+    "alumni",
+    "alumnus",
+    "anciens",
+    "egresados",
+    "exalumnos",
+    "graduates",
+];
+
+/// Second-level labels of academic suffixes, like `ac` in `ac.uk`
+const GENERIC_LABELS: &[&str] = &["ac", "co", "com", "edu", "gov", "net", "org", "sch"];
+
+impl AffiliationHint {
+    /// Affiliation hinted by the labels of a normalized domain, the leftmost
+    /// label that gives a hint wins.
+    ///
+    /// Only subdomains give hints, so `stu.edu.cn` of Shantou University does not.
+    ///
+    /// Graduation years are compared with the current year of the system clock,
+    /// except on `wasm32-unknown-unknown` where there is none and they give no
+    /// hint.
+    pub fn from_domain(domain: &str) -> AffiliationHint {
+        AffiliationHint::from_labels(domain, current_year)
+    }
+
+    /// Affiliation hinted by the labels of a normalized domain, with graduation
+    /// years compared with `current_year`, see [`from_domain`](AffiliationHint::from_domain)
+    pub fn from_domain_at(domain: &str, current_year: u16) -> AffiliationHint {
+        AffiliationHint::from_labels(domain, || Some(current_year))
+    }
+
+    /// The current year is only needed for graduation years, which are rare
+    fn from_labels(domain: &str, current_year: impl Fn() -> Option<u16>) -> AffiliationHint {
+        subdomain_labels(domain)
+            .map(|label| AffiliationHint::from_label(label, &current_year))
+            .find(|hint| *hint != AffiliationHint::Unknown)
+            .unwrap_or_default()
+    }
+
+    fn from_label(label: &str, current_year: impl Fn() -> Option<u16>) -> AffiliationHint {
+        if STUDENT_LABELS.contains(&label) {
+            AffiliationHint::Student
+        } else if STAFF_LABELS.contains(&label) {
+            AffiliationHint::Staff
+        } else if ALUMNI_LABELS.contains(&label) {
+            AffiliationHint::Alumni
+        } else if let Some(year) = cohort::label_year(label) {
+            match current_year() {
+                Some(current_year) if year < current_year => AffiliationHint::Alumni,
+                _ => AffiliationHint::Unknown,
+            }
+        } else {
+            AffiliationHint::Unknown
+        }
+    }
+
+    /// Stable snake case identifier of the hint
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AffiliationHint::Student => "student",
+            AffiliationHint::Staff => "staff",
+            AffiliationHint::Alumni => "alumni",
+            AffiliationHint::Unknown => "unknown",
+        }
+    }
+}
+
+/// Labels of a normalized domain, from the leftmost one, that are followed by
+/// the name of an institution, like `cs` in `cs.uonbi.ac.ke`
+pub(crate) fn subdomain_labels(domain: &str) -> impl Iterator<Item = &str> {
+    let mut rest = domain;
+    iter::from_fn(move || {
+        let (label, after) = rest.split_once('.')?;
+        rest = after;
+        Some((label, after))
+    })
+    .filter(|(_, after)| has_name(after))
+    .map(|(label, _)| label)
+}
+
+/// Current year of the system clock, in UTC
+fn current_year() -> Option<u16> {
+    // There is no clock on wasm32-unknown-unknown, `SystemTime::now` panics
+    if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        return None;
+    }
+    Some(year_of(SystemTime::now()))
+}
+
+/// Year of a time in UTC, with the days to civil algorithm of
+/// <https://howardhinnant.github.io/date_algorithms.html>
+fn year_of(time: SystemTime) -> u16 {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / 86_400);
+    // Days since 0000-03-01, so that leap days end the year
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Months from March
+    let month = (5 * day_of_year + 2) / 153;
+    let year = era * 400 + year_of_era + u64::from(month >= 10);
+    u16::try_from(year).unwrap_or(u16::MAX)
+}
+
+/// Whether a domain has a name before its top-level label, like `uonbi` in
+/// `uonbi.ac.ke`
+fn has_name(domain: &str) -> bool {
+    domain.rsplit_once('.').is_some_and(|(labels, _)| {
+        labels
+            .split('.')
+            .any(|label| !GENERIC_LABELS.contains(&label))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_from_domain() {
+        let tests = [
+            ("students.uonbi.ac.ke", AffiliationHint::Student),
+            ("stud.uni-corvinus.hu", AffiliationHint::Student),
+            ("alumnos.ugr.es", AffiliationHint::Student),
+            ("etu.univ-lyon1.fr", AffiliationHint::Student),
+            ("staff.uonbi.ac.ke", AffiliationHint::Staff),
+            ("faculty.stanford.edu", AffiliationHint::Staff),
+            ("alumni.stanford.edu", AffiliationHint::Alumni),
+            ("2011.cqut.edu.cn", AffiliationHint::Alumni),
+            ("class2019.stanford.edu", AffiliationHint::Alumni),
+            // Current students graduate this year or later
+            ("class2026.stanford.edu", AffiliationHint::Unknown),
+            ("class2030.stanford.edu", AffiliationHint::Unknown),
+            ("class2030.students.stanford.edu", AffiliationHint::Student),
+            ("2011.students.stanford.edu", AffiliationHint::Alumni),
+            ("alumni.students.stanford.edu", AffiliationHint::Alumni),
+            ("stanford.edu", AffiliationHint::Unknown),
+            ("cs.stanford.edu", AffiliationHint::Unknown),
+            ("stu.xmu.edu.cn", AffiliationHint::Student),
+            ("stu.edu.cn", AffiliationHint::Unknown),
+            ("stu.ac.kr", AffiliationHint::Unknown),
+            ("etu.ru", AffiliationHint::Unknown),
+            ("stud", AffiliationHint::Unknown),
+            ("3011.stanford.edu", AffiliationHint::Unknown),
+        ];
+        for (domain, hint) in tests {
+            assert_eq!(
+                AffiliationHint::from_domain_at(domain, 2026),
+                hint,
+                "{}",
+                domain
+            );
+        }
+
+        assert_eq!(
+            AffiliationHint::from_domain("2011.cqut.edu.cn"),
+            AffiliationHint::Alumni
+        );
+        assert_eq!(
+            AffiliationHint::from_domain("class3000.stanford.edu"),
+            AffiliationHint::Unknown
+        );
+    }
+
+    #[test]
+    fn test_year_of() {
+        let year = |seconds| year_of(UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!(year(0), 1970);
+        assert_eq!(year(951_782_400), 2000);
+        assert_eq!(year(1_767_225_599), 2025);
+        assert_eq!(year(1_767_225_600), 2026);
+        assert_eq!(year(1_790_000_000), 2026);
+        assert_eq!(year_of(UNIX_EPOCH - Duration::from_secs(1)), 1970);
+    }
+}
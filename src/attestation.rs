@@ -0,0 +1,496 @@
+//! Signed attestations of validations.
+//!
+//! A [`Signer`] turns a [`Validation`] into a compact token that another system
+//! can check with a [`Verifier`], without trusting the email it is handed. The
+//! token holds the hash of the email, the institution, the top-level domain,
+//! the version and hash of the dataset and an expiry.
+//!
+//! Emails are hashed with HMAC-SHA256 and an [`EmailKey`] that only the signer
+//! and the systems matching tokens with emails know: academic emails are easy
+//! to enumerate, so a plain hash could be reversed by anyone holding a token.
+//!
+//! Tokens are signed with HMAC-SHA256 or Ed25519 and name the key they are
+//! signed with, so keys can be rotated: the verifier accepts every key it
+//! knows, the signer moves to a new key, and the old key is dropped from the
+//! verifier once its tokens expired.
+//!
+//! A token is `<key id>.<payload>.<signature>`, with the payload and the
+//! signature in unpadded URL-safe base64.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::{Duration, SystemTime};
+//!
+//! use rswot::attestation::{EmailKey, Signer, VerifyError, Verifier};
+//!
+//! let email_key = EmailKey::new(b"another secret of at least 32....");
+//! let signer = Signer::hmac("2026-10", b"a secret of at least 32 bytes....", email_key.clone());
+//! let verifier = Verifier::new().with_hmac("2026-10", b"a secret of at least 32 bytes....");
+//!
+//! let now = SystemTime::now();
+//! let validation = rswot::validate("lreilly@stanford.edu").unwrap();
+//! let token = signer.sign(&validation, now, Duration::from_secs(3600));
+//!
+//! let attestation = verifier.verify(&token, now).unwrap();
+//! assert_eq!(attestation.tld, "edu");
+//! assert!(attestation.matches_email(&validation.email, &email_key));
+//! assert_eq!(
+//!     verifier.verify(&token, now + Duration::from_secs(7200)),
+//!     Err(VerifyError::Expired)
+//! );
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ed25519_dalek::{Signer as _, Verifier as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::dataset::{self, DatasetHash};
+use crate::{Email, Validation};
+
+/// Format of the payload, bumped when its fields change
+const PAYLOAD_VERSION: &str = "rswot2";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Reason a token was not verified
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VerifyError {
+    #[error("Token is malformed")]
+    Malformed,
+
+    #[error("Token is signed with an unknown key")]
+    UnknownKey,
+
+    #[error("Token signature is invalid")]
+    InvalidSignature,
+
+    #[error("Token is expired")]
+    Expired,
+}
+
+/// Claims of a verified token
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Attestation {
+    /// Key the token was signed with
+    pub key_id: String,
+    /// Keyed hash of the normalized email, see [`EmailKey::hash`]
+    pub email_hash: String,
+    /// Top-level domain of the email
+    pub tld: String,
+    /// First name of the institution, if known and embedded
+    pub institution: Option<String>,
+    /// Version of the crate and of its embedded dataset, see [`dataset::VERSION`]
+    pub dataset_version: String,
+    /// Hash of the dataset the email was validated against
+    pub dataset_hash: Option<DatasetHash>,
+    /// Seconds since the Unix epoch when the token was signed
+    pub issued_at: u64,
+    /// Seconds since the Unix epoch when the token expires
+    pub expires_at: u64,
+}
+
+impl Attestation {
+    /// Whether the token was issued for this email, with the email key of the
+    /// signer
+    pub fn matches_email(&self, email: &Email, email_key: &EmailKey) -> bool {
+        self.email_hash == email_key.hash(email)
+    }
+}
+
+/// Key of the hashes of emails in tokens, shared by the signer and the systems
+/// matching tokens with emails, which should be at least 32 random bytes
+///
+/// Anyone with the key can recover the email of a token from a list of emails,
+/// so it must be kept as secret as the emails.
+#[derive(Clone)]
+pub struct EmailKey(HmacSha256);
+
+impl EmailKey {
+    pub fn new(key: &[u8]) -> EmailKey {
+        EmailKey(HmacSha256::new_from_slice(key).expect("HMAC takes keys of any size"))
+    }
+
+    /// Lowercase hexadecimal HMAC-SHA256 of a normalized email
+    pub fn hash(&self, email: &Email) -> String {
+        let mut mac = self.0.clone();
+        mac.update(email.as_str().as_bytes());
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+impl fmt::Debug for EmailKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EmailKey(..)")
+    }
+}
+
+enum SigningKey {
+    Hmac(Vec<u8>),
+    Ed25519(ed25519_dalek::SigningKey),
+}
+
+/// Signs validations with a key
+pub struct Signer {
+    key_id: String,
+    key: SigningKey,
+    email_key: EmailKey,
+}
+
+impl Signer {
+    /// Sign with HMAC-SHA256 and a shared secret, which should be at least 32
+    /// random bytes, hashing emails with `email_key`
+    pub fn hmac(key_id: impl Into<String>, secret: &[u8], email_key: EmailKey) -> Signer {
+        Signer {
+            key_id: key_id.into(),
+            key: SigningKey::Hmac(secret.to_vec()),
+            email_key,
+        }
+    }
+
+    /// Sign with an Ed25519 secret key, verifiers only need its
+    /// [public key](Signer::ed25519_public_key), hashing emails with `email_key`
+    pub fn ed25519(
+        key_id: impl Into<String>,
+        secret_key: &[u8; 32],
+        email_key: EmailKey,
+    ) -> Signer {
+        Signer {
+            key_id: key_id.into(),
+            key: SigningKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(secret_key)),
+            email_key,
+        }
+    }
+
+    /// Id of the key, written in the tokens
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Public key to verify the tokens, `None` for HMAC
+    pub fn ed25519_public_key(&self) -> Option<[u8; 32]> {
+        match &self.key {
+            SigningKey::Hmac(_) => None,
+            SigningKey::Ed25519(key) => Some(key.verifying_key().to_bytes()),
+        }
+    }
+
+    /// Token attesting a validation, valid for `ttl` from `now`
+    pub fn sign(&self, validation: &Validation, now: SystemTime, ttl: Duration) -> String {
+        let issued_at = unix_seconds(now);
+        let institution = validation
+            .institution_names
+            .as_ref()
+            .and_then(|names| names.first())
+            .unwrap_or_default();
+        let dataset_hash = validation
+            .dataset_hash
+            .map(|hash| hash.to_string())
+            .unwrap_or_default();
+        let payload = [
+            PAYLOAD_VERSION,
+            &self.email_key.hash(&validation.email),
+            validation.tld.as_str(),
+            institution,
+            dataset::VERSION,
+            &dataset_hash,
+            &issued_at.to_string(),
+            &issued_at.saturating_add(ttl.as_secs()).to_string(),
+        ]
+        .join("\n");
+
+        let signed = format!("{}.{}", self.key_id, URL_SAFE_NO_PAD.encode(payload));
+        let signature = match &self.key {
+            SigningKey::Hmac(secret) => hmac(secret, &signed).finalize().into_bytes().to_vec(),
+            SigningKey::Ed25519(key) => key.sign(signed.as_bytes()).to_bytes().to_vec(),
+        };
+        format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(signature))
+    }
+}
+
+impl fmt::Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let algorithm = match self.key {
+            SigningKey::Hmac(_) => "hmac-sha256",
+            SigningKey::Ed25519(_) => "ed25519",
+        };
+        f.debug_struct("Signer")
+            .field("key_id", &self.key_id)
+            .field("algorithm", &algorithm)
+            .finish()
+    }
+}
+
+enum VerifyingKey {
+    Hmac(Vec<u8>),
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+/// Verifies tokens signed with any of its keys
+#[derive(Default)]
+pub struct Verifier {
+    keys: HashMap<String, VerifyingKey>,
+}
+
+impl Verifier {
+    /// Verifier without keys
+    pub fn new() -> Verifier {
+        Verifier::default()
+    }
+
+    /// Accept tokens signed with HMAC-SHA256 and a shared secret
+    pub fn with_hmac(mut self, key_id: impl Into<String>, secret: &[u8]) -> Verifier {
+        self.keys
+            .insert(key_id.into(), VerifyingKey::Hmac(secret.to_vec()));
+        self
+    }
+
+    /// Accept tokens signed with Ed25519, `None` if the public key is invalid
+    pub fn with_ed25519(
+        mut self,
+        key_id: impl Into<String>,
+        public_key: &[u8; 32],
+    ) -> Option<Verifier> {
+        let key = ed25519_dalek::VerifyingKey::from_bytes(public_key).ok()?;
+        self.keys.insert(key_id.into(), VerifyingKey::Ed25519(key));
+        Some(self)
+    }
+
+    /// Stop accepting tokens signed with a key, once it is rotated out
+    pub fn without_key(mut self, key_id: &str) -> Verifier {
+        self.keys.remove(key_id);
+        self
+    }
+
+    /// Check the signature and the expiry of a token at `now`, and return its
+    /// claims
+    pub fn verify(&self, token: &str, now: SystemTime) -> Result<Attestation, VerifyError> {
+        let (signed, signature) = token.rsplit_once('.').ok_or(VerifyError::Malformed)?;
+        let (key_id, payload) = signed.rsplit_once('.').ok_or(VerifyError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| VerifyError::Malformed)?;
+
+        let valid = match self.keys.get(key_id).ok_or(VerifyError::UnknownKey)? {
+            // Compared in constant time
+            VerifyingKey::Hmac(secret) => hmac(secret, signed).verify_slice(&signature).is_ok(),
+            VerifyingKey::Ed25519(key) => ed25519_dalek::Signature::from_slice(&signature)
+                .is_ok_and(|signature| key.verify(signed.as_bytes(), &signature).is_ok()),
+        };
+        if !valid {
+            return Err(VerifyError::InvalidSignature);
+        }
+
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|payload| String::from_utf8(payload).ok())
+            .ok_or(VerifyError::Malformed)?;
+        let attestation = parse_payload(key_id, &payload).ok_or(VerifyError::Malformed)?;
+        if unix_seconds(now) >= attestation.expires_at {
+            return Err(VerifyError::Expired);
+        }
+        Ok(attestation)
+    }
+}
+
+impl fmt::Debug for Verifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut key_ids: Vec<&String> = self.keys.keys().collect();
+        key_ids.sort_unstable();
+        f.debug_struct("Verifier")
+            .field("key_ids", &key_ids)
+            .finish()
+    }
+}
+
+fn parse_payload(key_id: &str, payload: &str) -> Option<Attestation> {
+    let fields: Vec<&str> = payload.split('\n').collect();
+    let [
+        PAYLOAD_VERSION,
+        email_hash,
+        tld,
+        institution,
+        dataset_version,
+        dataset_hash,
+        issued_at,
+        expires_at,
+    ] = fields[..]
+    else {
+        return None;
+    };
+    Some(Attestation {
+        key_id: key_id.to_string(),
+        email_hash: email_hash.to_string(),
+        tld: tld.to_string(),
+        institution: (!institution.is_empty()).then(|| institution.to_string()),
+        dataset_version: dataset_version.to_string(),
+        dataset_hash: match dataset_hash {
+            "" => None,
+            hash => Some(hash.parse().ok()?),
+        },
+        issued_at: issued_at.parse().ok()?,
+        expires_at: expires_at.parse().ok()?,
+    })
+}
+
+fn hmac(secret: &[u8], signed: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC takes keys of any size");
+    mac.update(signed.as_bytes());
+    mac
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"0123456789abcdef0123456789abcdef";
+    const EMAIL_KEY: &[u8] = b"fedcba9876543210fedcba9876543210";
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_790_000_000)
+    }
+
+    fn email_key() -> EmailKey {
+        EmailKey::new(EMAIL_KEY)
+    }
+
+    #[test]
+    fn test_hmac() {
+        let validation = crate::validate("Lreilly@Stanford.edu").unwrap();
+        let token = Signer::hmac("k1", SECRET, email_key()).sign(&validation, now(), HOUR);
+        assert!(token.starts_with("k1."));
+
+        let attestation = Verifier::new()
+            .with_hmac("k1", SECRET)
+            .verify(&token, now())
+            .unwrap();
+        assert_eq!(
+            attestation,
+            Attestation {
+                key_id: "k1".to_string(),
+                email_hash: email_key().hash(&validation.email),
+                tld: "edu".to_string(),
+                institution: dataset::HAS_INSTITUTION_NAMES
+                    .then(|| "Stanford University".to_string()),
+                dataset_version: dataset::VERSION.to_string(),
+                dataset_hash: Some(dataset::hash()),
+                issued_at: 1_790_000_000,
+                expires_at: 1_790_003_600,
+            }
+        );
+        assert!(attestation.matches_email(&validation.email, &email_key()));
+        assert!(
+            !attestation.matches_email(&Email::parse("lee@stanford.edu").unwrap(), &email_key())
+        );
+        // The hash depends on the key, not only on the email
+        assert_ne!(
+            attestation.email_hash,
+            EmailKey::new(b"another key").hash(&validation.email)
+        );
+        assert!(!attestation.matches_email(&validation.email, &EmailKey::new(b"another key")));
+    }
+
+    #[test]
+    fn test_ed25519() {
+        let signer = Signer::ed25519("k1", &[7; 32], email_key());
+        let public_key = signer.ed25519_public_key().unwrap();
+        let verifier = Verifier::new().with_ed25519("k1", &public_key).unwrap();
+
+        let validation = crate::validate("lee@uct.ac.za").unwrap();
+        let token = signer.sign(&validation, now(), HOUR);
+        let attestation = verifier.verify(&token, now()).unwrap();
+        assert_eq!(attestation.tld, "za");
+        assert_eq!(attestation.institution, None);
+
+        let other = Verifier::new()
+            .with_ed25519(
+                "k1",
+                &Signer::ed25519("k1", &[8; 32], email_key())
+                    .ed25519_public_key()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            other.verify(&token, now()),
+            Err(VerifyError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_verify_errors() {
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        let token = Signer::hmac("k1", SECRET, email_key()).sign(&validation, now(), HOUR);
+        let verifier = Verifier::new().with_hmac("k1", SECRET);
+
+        assert_eq!(
+            verifier.verify(&token, now() + HOUR),
+            Err(VerifyError::Expired)
+        );
+        assert_eq!(
+            Verifier::new()
+                .with_hmac("k2", SECRET)
+                .verify(&token, now()),
+            Err(VerifyError::UnknownKey)
+        );
+        assert_eq!(
+            Verifier::new()
+                .with_hmac("k1", b"another secret")
+                .verify(&token, now()),
+            Err(VerifyError::InvalidSignature)
+        );
+        assert_eq!(
+            verifier.verify("k1.e30", now()),
+            Err(VerifyError::Malformed)
+        );
+
+        // The payload is covered by the signature
+        let (key_id, rest) = token.split_once('.').unwrap();
+        let (_, signature) = rest.split_once('.').unwrap();
+        let forged = format!(
+            "{}.{}.{}",
+            key_id,
+            URL_SAFE_NO_PAD.encode("rswot2\nforged"),
+            signature
+        );
+        assert_eq!(
+            verifier.verify(&forged, now()),
+            Err(VerifyError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        let old = Signer::hmac("old", SECRET, email_key()).sign(&validation, now(), HOUR);
+        let new = Signer::hmac("new", b"a new secret", email_key()).sign(&validation, now(), HOUR);
+
+        let verifier = Verifier::new()
+            .with_hmac("old", SECRET)
+            .with_hmac("new", b"a new secret");
+        assert!(verifier.verify(&old, now()).is_ok());
+        assert!(verifier.verify(&new, now()).is_ok());
+
+        let verifier = verifier.without_key("old");
+        assert_eq!(verifier.verify(&old, now()), Err(VerifyError::UnknownKey));
+        assert!(verifier.verify(&new, now()).is_ok());
+    }
+}
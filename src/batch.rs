@@ -0,0 +1,210 @@
+use std::collections::{BTreeMap, HashMap};
+use std::vec;
+
+use crate::dataset::Dataset;
+use crate::{DomainValidation, Email, Error, Result, Tld, Validation};
+
+/// Number of emails that [`ValidateIter`] validates at once
+const CHUNK_SIZE: usize = 4096;
+
+/// Decision for each normalized domain already looked up
+type Decisions = HashMap<Box<str>, Result<DomainValidation>>;
+
+/// Result of the validation of a batch of emails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchValidation {
+    /// Result for each email, in input order
+    pub results: Vec<Result<Validation>>,
+    /// Aggregate counts over the results
+    pub summary: BatchSummary,
+}
+
+/// Aggregate counts over validation results
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BatchSummary {
+    /// Number of emails validated
+    pub total: usize,
+    /// Number of valid emails
+    pub valid: usize,
+    /// Number of invalid emails by error
+    pub errors: BTreeMap<Error, usize>,
+    /// Number of valid emails by top-level domain
+    pub tlds: BTreeMap<Tld, usize>,
+}
+
+impl BatchSummary {
+    /// Count a validation result
+    pub fn add(&mut self, result: &Result<Validation>) {
+        self.total += 1;
+        match result {
+            Ok(validation) => {
+                self.valid += 1;
+                *self.tlds.entry(validation.tld.clone()).or_default() += 1;
+            }
+            Err(error) => *self.errors.entry(error.clone()).or_default() += 1,
+        }
+    }
+}
+
+/// Iterator over the results of [`validate_iter`](crate::validate_iter)
+///
+/// Emails are validated by chunks, in parallel with the `rayon` feature.
+/// Decisions are kept for every domain seen so far, so memory grows with the
+/// number of distinct domains, not with the number of emails.
+#[derive(Debug)]
+pub struct ValidateIter<I> {
+    dataset: Dataset,
+    emails: I,
+    decisions: Decisions,
+    results: vec::IntoIter<Result<Validation>>,
+}
+
+impl<I> ValidateIter<I> {
+    pub(crate) fn new(dataset: Dataset, emails: I) -> ValidateIter<I> {
+        ValidateIter {
+            dataset,
+            emails,
+            decisions: Decisions::new(),
+            results: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<I> Iterator for ValidateIter<I>
+where
+    I: Iterator,
+    I::Item: TryInto<Email> + Send,
+    <I::Item as TryInto<Email>>::Error: Into<Error>,
+{
+    type Item = Result<Validation>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(result) = self.results.next() {
+            return Some(result);
+        }
+        let chunk: Vec<_> = self.emails.by_ref().take(CHUNK_SIZE).collect();
+        if chunk.is_empty() {
+            return None;
+        }
+        self.results = validate_chunk(&self.dataset, chunk, &mut self.decisions).into_iter();
+        self.results.next()
+    }
+}
+
+pub(crate) fn validate_batch<I>(dataset: &Dataset, emails: I) -> BatchValidation
+where
+    I: IntoIterator,
+    I::Item: TryInto<Email> + Send,
+    <I::Item as TryInto<Email>>::Error: Into<Error>,
+{
+    let emails = emails.into_iter().collect();
+    let results = validate_chunk(dataset, emails, &mut Decisions::new());
+    let mut summary = BatchSummary::default();
+    for result in &results {
+        summary.add(result);
+    }
+    BatchValidation { results, summary }
+}
+
+/// Validate emails, looking up each domain missing from `decisions` once
+fn validate_chunk<T>(
+    dataset: &Dataset,
+    emails: Vec<T>,
+    decisions: &mut Decisions,
+) -> Vec<Result<Validation>>
+where
+    T: TryInto<Email> + Send,
+    T::Error: Into<Error>,
+{
+    let emails = map(emails, |email| email.try_into().map_err(Into::into));
+
+    let mut domains: Vec<&str> = emails
+        .iter()
+        .filter_map(|email| email.as_ref().ok())
+        .map(Email::domain)
+        .filter(|domain| !decisions.contains_key(*domain))
+        .collect();
+    domains.sort_unstable();
+    domains.dedup();
+    let lookups = map(domains, |domain| {
+        (Box::from(domain), dataset.lookup(domain))
+    });
+    decisions.extend(lookups);
+
+    emails
+        .into_iter()
+        .map(|email| {
+            let email = email?;
+            let domain = decisions[email.domain()].clone()?;
+            Ok(Validation::new(email, domain))
+        })
+        .collect()
+}
+
+#[cfg(feature = "rayon")]
+fn map<T: Send, U: Send>(items: Vec<T>, f: impl Fn(T) -> U + Send + Sync) -> Vec<U> {
+    use rayon::prelude::*;
+
+    items.into_par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn map<T, U>(items: Vec<T>, f: impl Fn(T) -> U) -> Vec<U> {
+    items.into_iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMAILS: &[&str] = &[
+        "lreilly@stanford.edu",
+        "lee@gmail.com",
+        "john@Stanford.edu",
+        "the",
+        "lee@ugr.es",
+        "lee@leerilly.net",
+        "user@gmail.com",
+    ];
+
+    #[test]
+    fn test_validate_batch() {
+        let dataset = Dataset::embedded();
+        let batch = validate_batch(&dataset, EMAILS.iter().copied());
+
+        let expected: Vec<_> = EMAILS
+            .iter()
+            .map(|email| dataset.validate(*email))
+            .collect();
+        assert_eq!(batch.results, expected);
+
+        assert_eq!(batch.summary.total, 7);
+        assert_eq!(batch.summary.valid, 3);
+        assert_eq!(
+            batch.summary.errors,
+            BTreeMap::from([
+                (Error::InvalidEmail, 1),
+                (Error::FreeMailProvider, 2),
+                (Error::NotAcademic, 1),
+            ])
+        );
+        assert_eq!(
+            batch.summary.tlds,
+            BTreeMap::from([(Tld::EDU, 2), (Tld::SPAIN, 1)])
+        );
+    }
+
+    #[test]
+    fn test_validate_iter() {
+        let dataset = Dataset::embedded();
+        let emails = EMAILS.iter().copied().cycle().take(CHUNK_SIZE * 2 + 3);
+        let mut iter = ValidateIter::new(dataset.clone(), emails.clone());
+
+        for email in emails {
+            assert_eq!(iter.next(), Some(dataset.validate(email)));
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.decisions.len(), 4);
+    }
+}
@@ -0,0 +1,172 @@
+//! Bounded cache of domain decisions.
+//!
+//! Entries are evicted with the CLOCK algorithm, an approximation of LRU where
+//! a hit only sets a flag: hits share a read lock and only misses take the
+//! write lock, so the hot domains can be read concurrently.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::{DomainValidation, Result};
+
+/// Statistics of a domain decision cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CacheStats {
+    /// Lookups answered by the cache
+    pub hits: u64,
+    /// Lookups that went to the dataset
+    pub misses: u64,
+    /// Decisions dropped to make room for new ones
+    pub evictions: u64,
+    /// Decisions currently cached
+    pub len: usize,
+    /// Maximum number of decisions cached
+    pub capacity: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct DomainCache {
+    capacity: usize,
+    state: RwLock<State>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    index: HashMap<Box<str>, usize>,
+    slots: Vec<Slot>,
+    hand: usize,
+}
+
+#[derive(Debug)]
+struct Slot {
+    domain: Box<str>,
+    decision: Result<DomainValidation>,
+    referenced: AtomicBool,
+}
+
+impl DomainCache {
+    pub(crate) fn new(capacity: usize) -> DomainCache {
+        DomainCache {
+            capacity,
+            state: RwLock::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Cached decision for a normalized domain
+    pub(crate) fn get(&self, domain: &str) -> Option<Result<DomainValidation>> {
+        let state = self.state.read().unwrap();
+        let Some(&index) = state.index.get(domain) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        let slot = &state.slots[index];
+        slot.referenced.store(true, Ordering::Relaxed);
+        Some(slot.decision.clone())
+    }
+
+    pub(crate) fn insert(&self, domain: &str, decision: Result<DomainValidation>) {
+        let mut state = self.state.write().unwrap();
+        if state.index.contains_key(domain) {
+            return;
+        }
+        let slot = Slot {
+            domain: Box::from(domain),
+            decision,
+            referenced: AtomicBool::new(false),
+        };
+
+        let index = if state.slots.len() < self.capacity {
+            state.slots.push(slot);
+            state.slots.len() - 1
+        } else {
+            // Give referenced slots a second chance, evict the first one that is not
+            let index = loop {
+                let hand = state.hand;
+                state.hand = (hand + 1) % self.capacity;
+                if !state.slots[hand].referenced.swap(false, Ordering::Relaxed) {
+                    break hand;
+                }
+            };
+            let evicted = std::mem::replace(&mut state.slots[index], slot);
+            state.index.remove(&evicted.domain);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            index
+        };
+        let domain = state.slots[index].domain.clone();
+        state.index.insert(domain, index);
+    }
+
+    /// Drop every decision, statistics are kept
+    pub(crate) fn clear(&self) {
+        *self.state.write().unwrap() = State::default();
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            len: self.state.read().unwrap().slots.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Tld};
+
+    #[test]
+    fn test_get_and_insert() {
+        let cache = DomainCache::new(2);
+        assert_eq!(cache.get("stanford.edu"), None);
+        cache.insert("stanford.edu", Ok(DomainValidation::new(Tld::EDU)));
+        cache.insert("gmail.com", Err(Error::FreeMailProvider));
+        assert_eq!(
+            cache.get("stanford.edu"),
+            Some(Ok(DomainValidation::new(Tld::EDU)))
+        );
+        assert_eq!(cache.get("gmail.com"), Some(Err(Error::FreeMailProvider)));
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 2,
+                misses: 1,
+                evictions: 0,
+                len: 2,
+                capacity: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_eviction() {
+        let cache = DomainCache::new(2);
+        cache.insert("a.edu", Err(Error::NotAcademic));
+        cache.insert("b.edu", Err(Error::NotAcademic));
+        // Referenced entries get a second chance
+        assert!(cache.get("a.edu").is_some());
+        cache.insert("c.edu", Err(Error::NotAcademic));
+        assert!(cache.get("a.edu").is_some());
+        assert!(cache.get("b.edu").is_none());
+        assert!(cache.get("c.edu").is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.len, 2);
+
+        cache.clear();
+        assert!(cache.get("a.edu").is_none());
+        assert_eq!(cache.stats().len, 0);
+    }
+}
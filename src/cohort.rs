@@ -0,0 +1,120 @@
+use crate::affiliation;
+use crate::{Error, Result};
+
+/// Prefixes of cohort labels, like `class2019`
+const YEAR_PREFIXES: &[&str] = &["", "class", "grad", "promo"];
+
+/// Policy for cohort subdomains of institutions, like `2011.cqut.edu.cn` or
+/// `class2019.example.edu`
+///
+/// Cohort subdomains are often kept long after their students left, so they can
+/// be restricted to recent cohorts. The current year is supplied by the caller.
+///
+/// # Examples
+///
+/// ```
+/// use rswot::{CohortPolicy, Error};
+///
+/// let policy = CohortPolicy::within(2026, 6);
+/// assert!(policy.check("class2024.stanford.edu").is_ok());
+/// assert_eq!(
+///     policy.check("2011.stanford.edu"),
+///     Err(Error::Cohort { year: 2011 })
+/// );
+/// assert!(CohortPolicy::RejectAll.check("stanford.edu").is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CohortPolicy {
+    /// Accept cohorts at most `years` away from `current_year`
+    Within { current_year: u16, years: u16 },
+    /// Reject all cohorts
+    RejectAll,
+}
+
+impl CohortPolicy {
+    /// Accept cohorts at most `years` away from `current_year`, future
+    /// graduation years included
+    pub fn within(current_year: u16, years: u16) -> CohortPolicy {
+        CohortPolicy::Within {
+            current_year,
+            years,
+        }
+    }
+
+    /// Check the cohort of a normalized domain, if any.
+    ///
+    /// Will return [`Error::Cohort`] if the policy does not accept it.
+    pub fn check(&self, domain: &str) -> Result<()> {
+        let Some(year) = cohort_year(domain) else {
+            return Ok(());
+        };
+        let accepted = match *self {
+            CohortPolicy::Within {
+                current_year,
+                years,
+            } => year.abs_diff(current_year) <= years,
+            CohortPolicy::RejectAll => false,
+        };
+        if accepted {
+            Ok(())
+        } else {
+            Err(Error::Cohort { year })
+        }
+    }
+}
+
+/// Year of the leftmost cohort label of a normalized domain, only subdomains
+/// of an institution have one
+pub(crate) fn cohort_year(domain: &str) -> Option<u16> {
+    affiliation::subdomain_labels(domain).find_map(label_year)
+}
+
+/// Year of a label like `2011`, optionally prefixed like `class2011`
+pub(crate) fn label_year(label: &str) -> Option<u16> {
+    YEAR_PREFIXES.iter().find_map(|prefix| {
+        let year = label.strip_prefix(prefix)?;
+        let is_year = year.len() == 4
+            && (year.starts_with("19") || year.starts_with("20"))
+            && year.bytes().all(|b| b.is_ascii_digit());
+        if is_year { year.parse().ok() } else { None }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cohort_year() {
+        let tests = [
+            ("2011.cqut.edu.cn", Some(2011)),
+            ("class2019.stanford.edu", Some(2019)),
+            ("promo1998.cs.example.fr", Some(1998)),
+            ("cs.2019.stanford.edu", Some(2019)),
+            ("stanford.edu", None),
+            ("3011.stanford.edu", None),
+            ("class19.stanford.edu", None),
+            // Not a subdomain of an institution
+            ("2019.edu.cn", None),
+        ];
+        for (domain, year) in tests {
+            assert_eq!(cohort_year(domain), year, "{}", domain);
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let policy = CohortPolicy::within(2026, 4);
+        assert!(policy.check("2022.cqut.edu.cn").is_ok());
+        assert!(policy.check("class2030.stanford.edu").is_ok());
+        assert_eq!(
+            policy.check("2021.cqut.edu.cn"),
+            Err(Error::Cohort { year: 2021 })
+        );
+        assert_eq!(
+            CohortPolicy::RejectAll.check("class2026.stanford.edu"),
+            Err(Error::Cohort { year: 2026 })
+        );
+        assert!(CohortPolicy::RejectAll.check("cs.stanford.edu").is_ok());
+    }
+}
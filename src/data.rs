@@ -1,45 +1,131 @@
-include!(concat!(env!("OUT_DIR"), "/tlds.rs"));
-include!(concat!(env!("OUT_DIR"), "/stoplist.rs"));
-include!(concat!(env!("OUT_DIR"), "/abused.rs"));
-include!(concat!(env!("OUT_DIR"), "/institutions.rs"));
-
-pub fn get_institution_names(parts: &[&str]) -> Option<Vec<String>> {
-    let mut key = parts[0].to_string();
-
-    for part in parts.iter().skip(1) {
-        key.push('/');
-        key.push_str(part);
-        if let Some(names) = INSTITUTIONS.get(&key) {
-            return Some(names.iter().map(|s| s.to_string()).collect());
-        }
-    }
+//! Lookups in the datasets.
+//!
+//! The embedded dataset is stored as perfect hash maps by default, or as FSTs
+//! with the `fst` feature. Both backends return the same results.
+//! Datasets loaded at runtime are stored in hash maps.
+
+use std::iter;
+
+pub use self::runtime::RuntimeData;
+use crate::dataset::InstitutionNames;
+use crate::{AffiliationHint, DomainValidation, Error, Result, Tld};
+
+#[cfg(feature = "fst")]
+mod fst_backend;
+#[cfg(any(not(feature = "fst"), test))]
+mod phf_backend;
+mod runtime;
+
+#[cfg(feature = "fst")]
+pub use self::fst_backend::{
+    academic_suffixes, get_institution, get_tld, institutions, is_abused, is_disposable,
+    is_free_mail, is_stop_list,
+};
+#[cfg(not(feature = "fst"))]
+pub use self::phf_backend::{
+    academic_suffixes, get_institution, get_tld, institutions, is_abused, is_disposable,
+    is_free_mail, is_stop_list,
+};
 
-    None
+/// Lists of a dataset, looked up with normalized domains
+pub trait Lists {
+    fn is_stop_list(&self, domain: &str) -> bool;
+    fn is_abused(&self, domain: &str) -> bool;
+    fn is_free_mail(&self, domain: &str) -> bool;
+    fn is_disposable(&self, domain: &str) -> bool;
+    /// Top-level domain of the academic suffix (e.g. `ac.za`) the domain is under
+    fn get_tld(&self, domain: &str) -> Option<Tld>;
+    /// Institution registered for the domain or one of its parents
+    fn get_institution(&self, domain: &str) -> Option<(Tld, InstitutionNames)>;
 }
 
-pub fn is_under_tld(parts: &[&str]) -> bool {
-    check_set(&TLDS, parts)
+/// Embedded dataset
+pub struct Embedded;
+
+impl Lists for Embedded {
+    fn is_stop_list(&self, domain: &str) -> bool {
+        is_stop_list(domain)
+    }
+
+    fn is_abused(&self, domain: &str) -> bool {
+        is_abused(domain)
+    }
+
+    fn is_free_mail(&self, domain: &str) -> bool {
+        is_free_mail(domain)
+    }
+
+    fn is_disposable(&self, domain: &str) -> bool {
+        is_disposable(domain)
+    }
+
+    fn get_tld(&self, domain: &str) -> Option<Tld> {
+        get_tld(domain)
+    }
+
+    fn get_institution(&self, domain: &str) -> Option<(Tld, InstitutionNames)> {
+        get_institution(domain).map(|(tld, names)| (tld, InstitutionNames::embedded(names)))
+    }
 }
 
-pub fn is_stop_list(parts: &[&str]) -> bool {
-    check_set(&STOPLIST, parts)
+/// Look up a normalized domain in the lists of a dataset
+pub fn lookup(lists: &impl Lists, domain: &str) -> Result<DomainValidation> {
+    if lists.is_stop_list(domain) {
+        return Err(Error::Stop);
+    }
+
+    // Free mail providers come first, some of them are in the abused list
+    if lists.is_free_mail(domain) {
+        return Err(Error::FreeMailProvider);
+    }
+
+    if lists.is_disposable(domain) {
+        return Err(Error::DisposableProvider);
+    }
+
+    if lists.is_abused(domain) {
+        return Err(Error::AbusedAcademicDomain);
+    }
+
+    let validation = if let Some(tld) = lists.get_tld(domain) {
+        DomainValidation::new(tld)
+    } else if let Some((tld, institution_names)) = lists.get_institution(domain) {
+        DomainValidation::new(tld).with_institutions(institution_names)
+    } else {
+        return Err(match find_embedded(lists, domain) {
+            Some(target) => Error::Deceptive {
+                target: target.to_string(),
+            },
+            None => Error::NotAcademic,
+        });
+    };
+    Ok(validation.with_affiliation(AffiliationHint::from_domain(domain)))
 }
 
-pub fn is_abused(parts: &[&str]) -> bool {
-    check_set(&ABUSED, parts)
+/// Institution domain or academic suffix that ends before the top-level label
+/// of a domain, like `stanford.edu` in `stanford.edu.com`.
+///
+/// Institutions are preferred to academic suffixes, and the longest inner
+/// labels to shorter ones.
+fn find_embedded<'a>(lists: &impl Lists, domain: &'a str) -> Option<&'a str> {
+    domain
+        .rmatch_indices('.')
+        .map(|(i, _)| &domain[..i])
+        .find_map(|inner| {
+            suffixes(inner)
+                .find(|suffix| lists.get_institution(suffix).is_some())
+                .or_else(|| suffixes(inner).find(|suffix| lists.get_tld(suffix).is_some()))
+        })
 }
 
-fn check_set(set: &phf::Set<&str>, parts: &[&str]) -> bool {
-    let mut needle = String::new();
-    for &part in parts {
-        needle = format!("{}{}", part, needle);
-        if set.contains(&needle) {
-            return true;
-        } else {
-            needle = format!(".{}", needle);
-        }
-    }
-    false
+/// Iterate over the suffixes of a domain, from the top-level label to the full domain.
+///
+/// Every suffix is a slice of `domain`, no allocation is made.
+fn suffixes(domain: &str) -> impl Iterator<Item = &str> {
+    domain
+        .rmatch_indices('.')
+        .map(move |(i, _)| &domain[i + 1..])
+        .chain(iter::once(domain))
 }
 
 #[cfg(test)]
@@ -47,29 +133,149 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_is_under_tld() {
-        assert!(!is_under_tld(&["es", "ugr"]));
+    fn test_suffixes() {
+        assert_eq!(
+            suffixes("cs.strath.ac.uk").collect::<Vec<_>>(),
+            vec!["uk", "ac.uk", "strath.ac.uk", "cs.strath.ac.uk"]
+        );
+        assert_eq!(suffixes("edu").collect::<Vec<_>>(), vec!["edu"]);
+    }
+
+    #[test]
+    fn test_get_tld() {
+        assert_eq!(get_tld("ugr.es"), None);
+        assert_eq!(get_tld("uct.ac.za"), Some(Tld::new("za")));
     }
 
     #[test]
     fn test_is_stop_list() {
-        assert!(!is_stop_list(&["es", "ugr"]));
+        assert!(!is_stop_list("ugr.es"));
+    }
+
+    #[test]
+    fn test_lookup_abuse_categories() {
+        let tests = [
+            ("gmail.com", Error::FreeMailProvider),
+            ("mail.yahoo.co.uk", Error::FreeMailProvider),
+            ("mailinator.com", Error::DisposableProvider),
+            ("mdx.ac", Error::AbusedAcademicDomain),
+            ("foo.si.edu", Error::AbusedAcademicDomain),
+        ];
+        for (domain, error) in tests {
+            assert_eq!(lookup(&Embedded, domain), Err(error), "{}", domain);
+        }
+    }
+
+    #[test]
+    fn test_lookup_deceptive() {
+        let tests = [
+            ("stanford.edu.com", "stanford.edu"),
+            ("mail.stanford.edu.co.uk", "stanford.edu"),
+            ("strath.ac.uk.com", "strath.ac.uk"),
+            ("foo.ac.il.net", "ac.il"),
+            ("uct.ac.za.evil.org", "uct.ac.za"),
+        ];
+        for (domain, target) in tests {
+            let target = target.to_string();
+            assert_eq!(
+                lookup(&Embedded, domain),
+                Err(Error::Deceptive { target }),
+                "{}",
+                domain
+            );
+        }
+        for domain in ["leerilly.net", "edu.com", "stanford.com"] {
+            assert_eq!(
+                lookup(&Embedded, domain),
+                Err(Error::NotAcademic),
+                "{}",
+                domain
+            );
+        }
     }
 
+    #[cfg(feature = "names")]
     #[test]
     fn test_get_institution_names() {
         let tests = Vec::from([
             ("cs.strath.ac.uk", "University of Strathclyde"),
             ("fadi.at", "BRG Fadingerstraße Linz, Austria"),
-            ("abadojack@students.uonbi.ac.ke", "University of Nairobi"),
+            ("students.uonbi.ac.ke", "University of Nairobi"),
             ("harvard.edu", "Harvard University"),
             ("stanford.edu", "Stanford University"),
         ]);
 
         for (domain, name) in tests {
             println!("testing {}", domain);
-            let parts = domain.rsplit(".").collect::<Vec<&str>>();
-            assert_eq!(get_institution_names(&parts).unwrap()[0], name);
+            assert_eq!(get_institution(domain).unwrap().1[0], name);
+        }
+    }
+
+    #[cfg(feature = "fst")]
+    #[test]
+    fn test_backends_have_same_institutions() {
+        let mut phf_institutions = phf_backend::institutions().collect::<Vec<_>>();
+        let mut fst_institutions = fst_backend::institutions().collect::<Vec<_>>();
+        phf_institutions.sort();
+        fst_institutions.sort();
+        assert_eq!(phf_institutions, fst_institutions);
+
+        let mut phf_suffixes = phf_backend::academic_suffixes().collect::<Vec<_>>();
+        let mut fst_suffixes = fst_backend::academic_suffixes().collect::<Vec<_>>();
+        phf_suffixes.sort();
+        fst_suffixes.sort();
+        assert_eq!(phf_suffixes, fst_suffixes);
+    }
+
+    #[cfg(feature = "fst")]
+    #[test]
+    fn test_backends_are_equal() {
+        for domain in phf_backend::domains() {
+            let top_label = domain.rsplit('.').next().unwrap();
+            let candidates = [
+                domain.to_string(),
+                format!("mail.{}", domain),
+                format!("{}.com", domain),
+                top_label.to_string(),
+            ];
+            for candidate in &candidates {
+                assert_eq!(
+                    phf_backend::get_institution(candidate),
+                    fst_backend::get_institution(candidate),
+                    "institution of {}",
+                    candidate
+                );
+                assert_eq!(
+                    phf_backend::get_tld(candidate),
+                    fst_backend::get_tld(candidate),
+                    "tld of {}",
+                    candidate
+                );
+                assert_eq!(
+                    phf_backend::is_stop_list(candidate),
+                    fst_backend::is_stop_list(candidate),
+                    "stop list of {}",
+                    candidate
+                );
+                assert_eq!(
+                    phf_backend::is_abused(candidate),
+                    fst_backend::is_abused(candidate),
+                    "abused of {}",
+                    candidate
+                );
+                assert_eq!(
+                    phf_backend::is_free_mail(candidate),
+                    fst_backend::is_free_mail(candidate),
+                    "free mail of {}",
+                    candidate
+                );
+                assert_eq!(
+                    phf_backend::is_disposable(candidate),
+                    fst_backend::is_disposable(candidate),
+                    "disposable of {}",
+                    candidate
+                );
+            }
         }
     }
 }
@@ -0,0 +1,116 @@
+use std::sync::LazyLock;
+
+use fst::Streamer;
+use fst::raw::{Fst, Output};
+
+use crate::{Tld, instrument};
+
+include!(concat!(env!("OUT_DIR"), "/fst.rs"));
+
+static TLDS: LazyLock<Fst<&[u8]>> =
+    LazyLock::new(|| load(include_bytes!(concat!(env!("OUT_DIR"), "/tlds.fst"))));
+static STOPLIST: LazyLock<Fst<&[u8]>> =
+    LazyLock::new(|| load(include_bytes!(concat!(env!("OUT_DIR"), "/stoplist.fst"))));
+static ABUSED: LazyLock<Fst<&[u8]>> =
+    LazyLock::new(|| load(include_bytes!(concat!(env!("OUT_DIR"), "/abused.fst"))));
+static FREEMAIL: LazyLock<Fst<&[u8]>> =
+    LazyLock::new(|| load(include_bytes!(concat!(env!("OUT_DIR"), "/freemail.fst"))));
+static DISPOSABLE: LazyLock<Fst<&[u8]>> =
+    LazyLock::new(|| load(include_bytes!(concat!(env!("OUT_DIR"), "/disposable.fst"))));
+static INSTITUTIONS: LazyLock<Fst<&[u8]>> = LazyLock::new(|| {
+    load(include_bytes!(concat!(
+        env!("OUT_DIR"),
+        "/institutions.fst"
+    )))
+});
+
+fn load(bytes: &'static [u8]) -> Fst<&'static [u8]> {
+    Fst::new(bytes).expect("embedded FST is valid")
+}
+
+pub fn get_institution(domain: &str) -> Option<(Tld, &'static [&'static str])> {
+    find(&INSTITUTIONS, "institutions", domain, 2).map(|value| (top_label(value), names(value)))
+}
+
+pub fn institutions() -> impl Iterator<Item = (String, &'static [&'static str])> {
+    let mut institutions = Vec::new();
+    let mut stream = INSTITUTIONS.stream();
+    while let Some((key, output)) = stream.next() {
+        institutions.push((unreversed(key), names(output.value())));
+    }
+    institutions.into_iter()
+}
+
+pub fn academic_suffixes() -> impl Iterator<Item = String> {
+    let mut suffixes = Vec::new();
+    let mut stream = TLDS.stream();
+    while let Some((key, _)) = stream.next() {
+        suffixes.push(unreversed(key));
+    }
+    suffixes.into_iter()
+}
+
+/// Domain of a key, which has its labels reversed
+fn unreversed(key: &[u8]) -> String {
+    let key = String::from_utf8_lossy(key);
+    key.rsplit('.').collect::<Vec<_>>().join(".")
+}
+
+pub fn get_tld(domain: &str) -> Option<Tld> {
+    find(&TLDS, "academic_suffixes", domain, 1).map(top_label)
+}
+
+pub fn is_stop_list(domain: &str) -> bool {
+    find(&STOPLIST, "stoplist", domain, 1).is_some()
+}
+
+pub fn is_abused(domain: &str) -> bool {
+    find(&ABUSED, "abused", domain, 1).is_some()
+}
+
+pub fn is_free_mail(domain: &str) -> bool {
+    find(&FREEMAIL, "free_mail", domain, 1).is_some()
+}
+
+pub fn is_disposable(domain: &str) -> bool {
+    find(&DISPOSABLE, "disposable", domain, 1).is_some()
+}
+
+fn names(value: u64) -> &'static [&'static str] {
+    let offset = (value >> 32) as usize;
+    let len = ((value >> 16) & 0xffff) as usize;
+    &NAMES[offset..offset + len]
+}
+
+fn top_label(value: u64) -> Tld {
+    Tld::new(TOP_LABELS[(value & 0xffff) as usize])
+}
+
+/// Walk the labels of `domain` from the top-level one through the FST, which is keyed
+/// by reversed domains, and return the value of the shortest suffix of at least
+/// `min_labels` labels that is a key.
+///
+/// A single walk covers every suffix, no allocation is made.
+fn find(fst: &Fst<&[u8]>, list: &'static str, domain: &str, min_labels: usize) -> Option<u64> {
+    let mut node = fst.root();
+    let mut output = Output::zero();
+    let mut start = domain.len();
+
+    for (i, label) in domain.rsplit('.').enumerate() {
+        let separator = if i == 0 { None } else { Some(b'.') };
+        for byte in separator.into_iter().chain(label.bytes()) {
+            let transition = node.transition(node.find_input(byte)?);
+            output = output.cat(transition.out);
+            node = fst.node(transition.addr);
+        }
+        start -= label.len() + usize::from(i > 0);
+        if i + 1 >= min_labels {
+            instrument::probe(list, &domain[start..], node.is_final());
+            if node.is_final() {
+                return Some(output.cat(node.final_output()).value());
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,80 @@
+use super::suffixes;
+use crate::{Tld, instrument};
+
+include!(concat!(env!("OUT_DIR"), "/tlds.rs"));
+include!(concat!(env!("OUT_DIR"), "/stoplist.rs"));
+include!(concat!(env!("OUT_DIR"), "/abused.rs"));
+include!(concat!(env!("OUT_DIR"), "/freemail.rs"));
+include!(concat!(env!("OUT_DIR"), "/disposable.rs"));
+include!(concat!(env!("OUT_DIR"), "/institutions.rs"));
+
+pub fn get_institution(domain: &str) -> Option<(Tld, &'static [&'static str])> {
+    suffixes(domain)
+        .skip(1)
+        .find_map(|suffix| {
+            let entry = INSTITUTIONS.get_entry(suffix);
+            instrument::probe("institutions", suffix, entry.is_some());
+            entry
+        })
+        .map(|(key, names)| (top_label(key), *names))
+}
+
+pub fn institutions() -> impl Iterator<Item = (String, &'static [&'static str])> {
+    INSTITUTIONS
+        .entries()
+        .map(|(key, names)| (key.to_string(), *names))
+}
+
+pub fn academic_suffixes() -> impl Iterator<Item = String> {
+    TLDS.iter().map(|suffix| suffix.to_string())
+}
+
+pub fn get_tld(domain: &str) -> Option<Tld> {
+    check_set(&TLDS, "academic_suffixes", domain).map(top_label)
+}
+
+pub fn is_stop_list(domain: &str) -> bool {
+    check_set(&STOPLIST, "stoplist", domain).is_some()
+}
+
+pub fn is_abused(domain: &str) -> bool {
+    check_set(&ABUSED, "abused", domain).is_some()
+}
+
+pub fn is_free_mail(domain: &str) -> bool {
+    check_set(&FREEMAIL, "free_mail", domain).is_some()
+}
+
+pub fn is_disposable(domain: &str) -> bool {
+    check_set(&DISPOSABLE, "disposable", domain).is_some()
+}
+
+/// Top-level domain of a dataset key, borrowed without allocating
+fn top_label(key: &'static str) -> Tld {
+    Tld::new(key.rsplit('.').next().unwrap_or(key))
+}
+
+fn check_set(
+    set: &phf::Set<&'static str>,
+    list: &'static str,
+    domain: &str,
+) -> Option<&'static str> {
+    suffixes(domain).find_map(|suffix| {
+        let key = set.get_key(suffix).copied();
+        instrument::probe(list, suffix, key.is_some());
+        key
+    })
+}
+
+/// Every domain of the dataset, used to compare backends
+#[cfg(all(test, feature = "fst"))]
+pub(super) fn domains() -> impl Iterator<Item = &'static str> {
+    INSTITUTIONS
+        .keys()
+        .chain(TLDS.iter())
+        .chain(STOPLIST.iter())
+        .chain(ABUSED.iter())
+        .chain(FREEMAIL.iter())
+        .chain(DISPOSABLE.iter())
+        .copied()
+}
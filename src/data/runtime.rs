@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use super::{Lists, is_disposable, is_free_mail, suffixes};
+use crate::dataset::{
+    self, Counts, DatasetHash, HAS_INSTITUTION_NAMES, InstitutionNames, ListHashes,
+};
+use crate::index::DomainIndex;
+use crate::{Tld, instrument};
+
+const SKIP_FILES: &[&str] = &[
+    "tlds.txt",
+    "abused.txt",
+    "stoplist.txt",
+    "freemail.txt",
+    "disposable.txt",
+];
+
+/// Top-level domains of a dataset being loaded, shared by its entries
+#[derive(Default)]
+struct TopLabels(HashMap<Box<str>, Tld>);
+
+impl TopLabels {
+    fn get(&mut self, domain: &str) -> Tld {
+        let top_label = domain.rsplit('.').next().unwrap_or(domain);
+        if let Some(tld) = self.0.get(top_label) {
+            return tld.clone();
+        }
+        let tld = Tld::shared(top_label.into());
+        self.0.insert(top_label.into(), tld.clone());
+        tld
+    }
+}
+
+/// Dataset loaded at runtime from a directory laid out like `domains`
+///
+/// The free mail and disposable lists are not part of `domains`, the embedded
+/// ones are used unless the directory has a `freemail.txt` or `disposable.txt`.
+pub struct RuntimeData {
+    tlds: HashMap<Box<str>, Tld>,
+    stoplist: HashSet<Box<str>>,
+    abused: HashSet<Box<str>>,
+    free_mail: Option<HashSet<Box<str>>>,
+    disposable: Option<HashSet<Box<str>>>,
+    institutions: HashMap<Box<str>, (Tld, InstitutionNames)>,
+    hash: DatasetHash,
+    /// Index of the institutions and suffixes for lookalike searches and
+    /// corrections, built on first use
+    index: OnceLock<DomainIndex>,
+}
+
+impl RuntimeData {
+    pub fn load(dir: &Path) -> io::Result<RuntimeData> {
+        let mut top_labels = TopLabels::default();
+        let mut institutions = HashMap::new();
+        read_institutions(dir, dir, &mut top_labels, &mut institutions)?;
+        let tlds: HashMap<_, _> = read_set(&dir.join("tlds.txt"))?
+            .into_iter()
+            .map(|tld| {
+                let top_label = top_labels.get(&tld);
+                (tld, top_label)
+            })
+            .collect();
+        let stoplist = read_set(&dir.join("stoplist.txt"))?;
+        let abused = read_set(&dir.join("abused.txt"))?;
+        let free_mail = read_optional_set(&dir.join("freemail.txt"))?;
+        let disposable = read_optional_set(&dir.join("disposable.txt"))?;
+
+        let embedded = ListHashes::embedded();
+        let hash = ListHashes {
+            institutions: hash_keys(institutions.keys()),
+            academic_suffixes: hash_keys(tlds.keys()),
+            stoplist: hash_keys(stoplist.iter()),
+            abused: hash_keys(abused.iter()),
+            free_mail: free_mail
+                .as_ref()
+                .map_or(embedded.free_mail, |set| hash_keys(set.iter())),
+            disposable: disposable
+                .as_ref()
+                .map_or(embedded.disposable, |set| hash_keys(set.iter())),
+        }
+        .dataset_hash();
+
+        Ok(RuntimeData {
+            tlds,
+            stoplist,
+            abused,
+            free_mail,
+            disposable,
+            institutions,
+            hash,
+            index: OnceLock::new(),
+        })
+    }
+
+    pub fn counts(&self) -> Counts {
+        let embedded = dataset::counts();
+        Counts {
+            institutions: self.institutions.len(),
+            academic_suffixes: self.tlds.len(),
+            stoplist: self.stoplist.len(),
+            abused: self.abused.len(),
+            free_mail: self
+                .free_mail
+                .as_ref()
+                .map_or(embedded.free_mail, HashSet::len),
+            disposable: self
+                .disposable
+                .as_ref()
+                .map_or(embedded.disposable, HashSet::len),
+        }
+    }
+
+    pub fn hash(&self) -> DatasetHash {
+        self.hash
+    }
+
+    pub fn index(&self) -> &DomainIndex {
+        self.index.get_or_init(|| {
+            DomainIndex::new(
+                self.institutions().map(|(domain, _)| domain),
+                self.academic_suffixes(),
+            )
+        })
+    }
+
+    pub fn academic_suffixes(&self) -> impl Iterator<Item = String> {
+        self.tlds.keys().map(|suffix| suffix.to_string())
+    }
+
+    pub fn institutions(&self) -> impl Iterator<Item = (String, InstitutionNames)> {
+        self.institutions
+            .iter()
+            .map(|(domain, (_, names))| (domain.to_string(), names.clone()))
+    }
+}
+
+impl Lists for RuntimeData {
+    fn is_stop_list(&self, domain: &str) -> bool {
+        contains(&self.stoplist, "stoplist", domain)
+    }
+
+    fn is_abused(&self, domain: &str) -> bool {
+        contains(&self.abused, "abused", domain)
+    }
+
+    fn is_free_mail(&self, domain: &str) -> bool {
+        match &self.free_mail {
+            Some(free_mail) => contains(free_mail, "free_mail", domain),
+            None => is_free_mail(domain),
+        }
+    }
+
+    fn is_disposable(&self, domain: &str) -> bool {
+        match &self.disposable {
+            Some(disposable) => contains(disposable, "disposable", domain),
+            None => is_disposable(domain),
+        }
+    }
+
+    fn get_tld(&self, domain: &str) -> Option<Tld> {
+        suffixes(domain).find_map(|suffix| {
+            let tld = self.tlds.get(suffix).cloned();
+            instrument::probe("academic_suffixes", suffix, tld.is_some());
+            tld
+        })
+    }
+
+    fn get_institution(&self, domain: &str) -> Option<(Tld, InstitutionNames)> {
+        suffixes(domain).skip(1).find_map(|suffix| {
+            let institution = self.institutions.get(suffix).cloned();
+            instrument::probe("institutions", suffix, institution.is_some());
+            institution
+        })
+    }
+}
+
+/// Whether a suffix of a domain is in a list
+fn contains(set: &HashSet<Box<str>>, list: &'static str, domain: &str) -> bool {
+    suffixes(domain).any(|suffix| {
+        let matched = set.contains(suffix);
+        instrument::probe(list, suffix, matched);
+        matched
+    })
+}
+
+impl fmt::Debug for RuntimeData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeData")
+            .field("counts", &self.counts())
+            .field("hash", &self.hash)
+            .finish()
+    }
+}
+
+// Files are read like build.rs does, so loading the `domains` directory the crate
+// was built with gives the same dataset as the embedded one
+
+fn hash_keys<'a>(keys: impl Iterator<Item = &'a Box<str>>) -> u64 {
+    dataset::hash_entries(keys.map(|key| &**key))
+}
+
+fn read_set(path: &Path) -> io::Result<HashSet<Box<str>>> {
+    Ok(BufReader::new(File::open(path)?)
+        .lines()
+        .filter_map(|line| line.ok().map(Box::from))
+        .collect())
+}
+
+fn read_optional_set(path: &Path) -> io::Result<Option<HashSet<Box<str>>>> {
+    match read_set(path) {
+        Ok(set) => Ok(Some(set)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn read_institutions(
+    dir: &Path,
+    root: &Path,
+    top_labels: &mut TopLabels,
+    institutions: &mut HashMap<Box<str>, (Tld, InstitutionNames)>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            read_institutions(&path, root, top_labels, institutions)?;
+            continue;
+        }
+        if path.extension().is_none_or(|e| e != "txt") {
+            continue;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if dir == root && SKIP_FILES.contains(&name) {
+            continue;
+        }
+
+        let key = path.strip_prefix(root).unwrap_or(&path).with_extension("");
+        let key = key
+            .components()
+            .rev()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(".");
+
+        let mut names = Vec::new();
+        if HAS_INSTITUTION_NAMES {
+            for line in BufReader::new(File::open(&path)?)
+                .lines()
+                .map_while(Result::ok)
+            {
+                let line = line.trim();
+                if !line.is_empty() {
+                    names.push(Box::from(line));
+                }
+            }
+        }
+
+        let names = if names.is_empty() {
+            InstitutionNames::default()
+        } else {
+            InstitutionNames::loaded(names.into())
+        };
+        institutions.insert(key.as_str().into(), (top_labels.get(&key), names));
+    }
+    Ok(())
+}
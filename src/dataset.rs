@@ -0,0 +1,612 @@
+//! Datasets to validate emails against, and information about the embedded one.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::ops::Index;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::batch::{self, BatchValidation, ValidateIter};
+use crate::data::{self, Embedded, RuntimeData};
+use crate::{
+    DomainValidation, Email, Error, Result, Validation, email, impersonation, index, instrument,
+    suggest,
+};
+
+include!(concat!(env!("OUT_DIR"), "/dataset.rs"));
+
+/// Version of the dataset.
+///
+/// The crate gets a new patch release every time the dataset is synced from
+/// [JetBrains/swot](https://github.com/JetBrains/swot), so this is the crate version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Whether institution names are embedded.
+///
+/// Names are stripped when the crate is built without the `names` feature,
+/// in which case [`Validation::institution_names`](crate::Validation::institution_names)
+/// is empty for emails of known institutions.
+pub const HAS_INSTITUTION_NAMES: bool = cfg!(feature = "names");
+
+/// Top-level domains that have institutions or academic suffixes in the dataset.
+///
+/// Only the top-level domains selected by the `tlds-*` features are embedded,
+/// or all of them if no such feature is enabled.
+pub fn top_level_domains() -> &'static [&'static str] {
+    TOP_LEVEL_DOMAINS
+}
+
+/// Maximum number of corrections returned by [`Dataset::suggest`]
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Number of entries in each list of the dataset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Counts {
+    /// Institution domains
+    pub institutions: usize,
+    /// Academic suffixes, like `ac.za`
+    pub academic_suffixes: usize,
+    /// Domains marked as stop
+    pub stoplist: usize,
+    /// Known abusive domains
+    pub abused: usize,
+    /// Free mail providers
+    pub free_mail: usize,
+    /// Disposable mail providers
+    pub disposable: usize,
+}
+
+/// Number of entries in each list of the dataset
+pub fn counts() -> Counts {
+    Counts {
+        institutions: INSTITUTIONS_COUNT,
+        academic_suffixes: TLDS_COUNT,
+        stoplist: STOPLIST_COUNT,
+        abused: ABUSED_COUNT,
+        free_mail: FREEMAIL_COUNT,
+        disposable: DISPOSABLE_COUNT,
+    }
+}
+
+/// Hash of the domains of a dataset, institution names excluded
+///
+/// Stored along with a [`Validation`] to tell whether the dataset changed since.
+/// Written as 16 hexadecimal digits, and serialized as such with the `serde`
+/// feature.
+///
+/// # Examples
+///
+/// ```
+/// use rswot::dataset::{self, DatasetHash};
+///
+/// let hash = dataset::hash();
+/// assert_eq!(hash.to_string().parse::<DatasetHash>(), Ok(hash));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DatasetHash(u64);
+
+impl fmt::Display for DatasetHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for DatasetHash {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<DatasetHash, Self::Err> {
+        u64::from_str_radix(s, 16).map(DatasetHash)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DatasetHash {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Hashes of each list of a dataset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ListHashes {
+    pub institutions: u64,
+    pub academic_suffixes: u64,
+    pub stoplist: u64,
+    pub abused: u64,
+    pub free_mail: u64,
+    pub disposable: u64,
+}
+
+impl ListHashes {
+    pub(crate) fn embedded() -> ListHashes {
+        ListHashes {
+            institutions: INSTITUTIONS_HASH,
+            academic_suffixes: TLDS_HASH,
+            stoplist: STOPLIST_HASH,
+            abused: ABUSED_HASH,
+            free_mail: FREEMAIL_HASH,
+            disposable: DISPOSABLE_HASH,
+        }
+    }
+
+    pub(crate) fn dataset_hash(&self) -> DatasetHash {
+        let lists = [
+            self.institutions,
+            self.academic_suffixes,
+            self.stoplist,
+            self.abused,
+            self.free_mail,
+            self.disposable,
+        ];
+        DatasetHash(fnv1a(lists.iter().flat_map(|hash| hash.to_le_bytes())))
+    }
+}
+
+/// Hash of the entries of a list whatever their order, like build.rs does
+pub(crate) fn hash_entries<'a>(entries: impl Iterator<Item = &'a str>) -> u64 {
+    entries
+        .map(|entry| fnv1a(entry.bytes()))
+        .fold(0, u64::wrapping_add)
+}
+
+fn fnv1a(bytes: impl Iterator<Item = u8>) -> u64 {
+    bytes.fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Hash of the embedded dataset
+pub fn hash() -> DatasetHash {
+    ListHashes::embedded().dataset_hash()
+}
+
+/// Institution of the dataset
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Institution {
+    /// Domain of the institution, its subdomains belong to it too
+    pub domain: String,
+    /// Names of the institution
+    pub names: InstitutionNames,
+}
+
+/// Names of an institution, borrowed from the embedded dataset or shared with
+/// the loaded dataset they come from. Cloning is cheap.
+///
+/// # Examples
+///
+/// ```
+/// # if !rswot::dataset::HAS_INSTITUTION_NAMES { return; }
+/// let names = rswot::dataset::Dataset::embedded()
+///     .institution_names("cs.stanford.edu")
+///     .unwrap();
+/// assert_eq!(names, ["Stanford University"]);
+/// assert_eq!(&names[0], "Stanford University");
+/// ```
+#[derive(Clone)]
+pub struct InstitutionNames(Names);
+
+#[derive(Clone)]
+enum Names {
+    Embedded(&'static [&'static str]),
+    Loaded(Arc<[Box<str>]>),
+}
+
+impl InstitutionNames {
+    pub(crate) const fn embedded(names: &'static [&'static str]) -> InstitutionNames {
+        InstitutionNames(Names::Embedded(names))
+    }
+
+    pub(crate) fn loaded(names: Arc<[Box<str>]>) -> InstitutionNames {
+        InstitutionNames(Names::Loaded(names))
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Names::Embedded(names) => names.len(),
+            Names::Loaded(names) => names.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        match &self.0 {
+            Names::Embedded(names) => names.get(index).copied(),
+            Names::Loaded(names) => names.get(index).map(|name| &**name),
+        }
+    }
+
+    pub fn first(&self) -> Option<&str> {
+        self.get(0)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &str> {
+        (0..self.len()).map(|i| &self[i])
+    }
+}
+
+impl Default for InstitutionNames {
+    fn default() -> InstitutionNames {
+        InstitutionNames::embedded(&[])
+    }
+}
+
+impl Index<usize> for InstitutionNames {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        match &self.0 {
+            Names::Embedded(names) => names[index],
+            Names::Loaded(names) => &names[index],
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a InstitutionNames {
+    type Item = &'a str;
+    type IntoIter = Box<dyn ExactSizeIterator<Item = &'a str> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl fmt::Debug for InstitutionNames {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for InstitutionNames {
+    fn eq(&self, other: &InstitutionNames) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for InstitutionNames {}
+
+impl<const N: usize> PartialEq<[&str; N]> for InstitutionNames {
+    fn eq(&self, other: &[&str; N]) -> bool {
+        self.iter().eq(other.iter().copied())
+    }
+}
+
+impl Hash for InstitutionNames {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        self.iter().for_each(|name| name.hash(state));
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for InstitutionNames {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Iterate over the institutions of the dataset, in no particular order.
+pub fn institutions() -> impl Iterator<Item = Institution> {
+    data::institutions().map(|(domain, names)| Institution {
+        domain,
+        names: InstitutionNames::embedded(names),
+    })
+}
+
+/// Dataset to validate emails against.
+///
+/// Either the embedded dataset, used by [`validate`](crate::validate), or a dataset
+/// loaded at runtime from a directory laid out like the `domains` directory of
+/// [JetBrains/swot](https://github.com/JetBrains/swot), to pick up updates without
+/// rebuilding. Cloning is cheap.
+///
+/// Institution names of loaded datasets are shared with the validations made
+/// against them, and freed with the last of them once the dataset is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use rswot::dataset::Dataset;
+///
+/// let dataset = Dataset::embedded();
+/// assert!(dataset.validate("lreilly@stanford.edu").is_ok());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    loaded: Option<Arc<RuntimeData>>,
+}
+
+impl Dataset {
+    /// Dataset embedded at build time
+    pub fn embedded() -> Dataset {
+        Dataset { loaded: None }
+    }
+
+    /// Load a dataset from a directory laid out like `domains`: `tlds.txt`,
+    /// `stoplist.txt` and `abused.txt` at the root and one file per institution
+    /// in directories named after the labels of its domain.
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Dataset> {
+        let loaded = RuntimeData::load(dir.as_ref())?;
+        Ok(Dataset {
+            loaded: Some(Arc::new(loaded)),
+        })
+    }
+
+    /// Whether this is the embedded dataset
+    pub fn is_embedded(&self) -> bool {
+        self.loaded.is_none()
+    }
+
+    /// Validate an email address, see [`validate`](crate::validate).
+    pub fn validate<T>(&self, email: T) -> Result<Validation>
+    where
+        T: TryInto<Email>,
+        T::Error: Into<Error>,
+    {
+        let email = email.try_into().map_err(Into::into)?;
+        let _span = instrument::validate_span(&email);
+        let outcome = self
+            .lookup(email.domain())
+            .map(|domain| Validation::new(email, domain));
+        instrument::decision(&outcome);
+        outcome
+    }
+
+    /// Validate a batch of emails, see [`validate_batch`](crate::validate_batch).
+    pub fn validate_batch<I>(&self, emails: I) -> BatchValidation
+    where
+        I: IntoIterator,
+        I::Item: TryInto<Email> + Send,
+        <I::Item as TryInto<Email>>::Error: Into<Error>,
+    {
+        batch::validate_batch(self, emails)
+    }
+
+    /// Validate emails lazily, see [`validate_iter`](crate::validate_iter).
+    pub fn validate_iter<I>(&self, emails: I) -> ValidateIter<I::IntoIter>
+    where
+        I: IntoIterator,
+    {
+        ValidateIter::new(self.clone(), emails.into_iter())
+    }
+
+    /// Validate a domain name, see [`validate_domain`](crate::validate_domain).
+    pub fn validate_domain(&self, domain: &str) -> Result<DomainValidation> {
+        self.lookup(&email::parse_domain(domain)?)
+    }
+
+    /// Look up the names of the institution that a domain belongs to,
+    /// see [`institution_names`](crate::institution_names).
+    pub fn institution_names(&self, domain: &str) -> Option<InstitutionNames> {
+        let domain = email::parse_domain(domain).ok()?;
+        match &self.loaded {
+            Some(loaded) => data::Lists::get_institution(&**loaded, &domain),
+            None => data::Lists::get_institution(&Embedded, &domain),
+        }
+        .map(|(_, names)| names)
+    }
+
+    /// Domain of the institution that a domain looks like, if the domain is
+    /// not academic.
+    ///
+    /// Catches typos (`stanfrod.edu`), homoglyphs (`stanf0rd.edu`), institution
+    /// domains embedded in another one (`harvard-edu.org`) and transposed labels
+    /// (`edu.stanford.com`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rswot::dataset::Dataset;
+    ///
+    /// let dataset = Dataset::embedded();
+    /// assert_eq!(
+    ///     dataset.impersonated_institution("stanfrod.edu.co").as_deref(),
+    ///     Some("stanford.edu")
+    /// );
+    /// assert_eq!(dataset.impersonated_institution("stanford.edu"), None);
+    /// ```
+    pub fn impersonated_institution(&self, domain: &str) -> Option<String> {
+        let domain = email::parse_domain(domain).ok()?;
+        match self.lookup(&domain) {
+            Err(Error::NotAcademic) => self.find_impersonated(&domain),
+            _ => None,
+        }
+    }
+
+    /// Look up a normalized domain that is not academic among the institutions
+    pub(crate) fn find_impersonated(&self, domain: &str) -> Option<String> {
+        impersonation::find(self.index(), domain).map(str::to_string)
+    }
+
+    /// Likely corrections of an email whose domain is not academic, the closest
+    /// first.
+    ///
+    /// Corrections are drawn from the institution domains and academic suffixes
+    /// of the dataset, by an edit distance where typing mistakes like swapped
+    /// letters, doubled letters and neighbouring keys are cheap. There are at most
+    /// [`MAX_SUGGESTIONS`] of them, all valid, and none for invalid emails or
+    /// emails that are not rejected as [`Error::NotAcademic`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rswot::dataset::Dataset;
+    ///
+    /// let dataset = Dataset::embedded();
+    /// let suggestions = dataset.suggest("lreilly@stanford.eud");
+    /// assert_eq!(suggestions[0].to_string(), "lreilly@stanford.edu");
+    /// assert!(dataset.suggest("lreilly@stanford.edu").is_empty());
+    /// ```
+    pub fn suggest<T>(&self, email: T) -> Vec<Email>
+    where
+        T: TryInto<Email>,
+    {
+        let Ok(email) = email.try_into() else {
+            return Vec::new();
+        };
+        if !matches!(self.lookup(email.domain()), Err(Error::NotAcademic)) {
+            return Vec::new();
+        }
+
+        suggest::find(self.index(), email.domain(), |domain| {
+            self.lookup(domain).is_ok()
+        })
+        .into_iter()
+        .filter_map(|domain| Email::parse(&format!("{}@{}", email.user(), domain)).ok())
+        .take(MAX_SUGGESTIONS)
+        .collect()
+    }
+
+    /// Hash of the domains of the dataset, a loaded dataset has the same hash as
+    /// the embedded one if it has the same domains
+    pub fn hash(&self) -> DatasetHash {
+        match &self.loaded {
+            Some(loaded) => loaded.hash(),
+            None => hash(),
+        }
+    }
+
+    /// Number of entries in each list of the dataset
+    pub fn counts(&self) -> Counts {
+        match &self.loaded {
+            Some(loaded) => loaded.counts(),
+            None => counts(),
+        }
+    }
+
+    /// Iterate over the institutions of the dataset, in no particular order.
+    pub fn institutions(&self) -> Box<dyn Iterator<Item = Institution> + '_> {
+        match &self.loaded {
+            Some(loaded) => Box::new(
+                loaded
+                    .institutions()
+                    .map(|(domain, names)| Institution { domain, names }),
+            ),
+            None => Box::new(institutions()),
+        }
+    }
+
+    /// Index of the institution domains and academic suffixes, built on first use
+    pub(crate) fn index(&self) -> &index::DomainIndex {
+        match &self.loaded {
+            Some(loaded) => loaded.index(),
+            None => index::embedded(),
+        }
+    }
+
+    /// Look up a normalized domain
+    pub(crate) fn lookup(&self, domain: &str) -> Result<DomainValidation> {
+        let validation = match &self.loaded {
+            Some(loaded) => data::lookup(&**loaded, domain),
+            None => data::lookup(&Embedded, domain),
+        }?;
+        Ok(validation.with_dataset_hash(self.hash()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_level_domains() {
+        assert!(top_level_domains().contains(&"edu"));
+        assert!(top_level_domains().is_sorted());
+    }
+
+    #[test]
+    fn test_loaded_dataset_matches_embedded() {
+        let embedded = Dataset::embedded();
+        let loaded = Dataset::load(concat!(env!("CARGO_MANIFEST_DIR"), "/domains")).unwrap();
+        assert!(!loaded.is_embedded());
+
+        // The embedded dataset may be restricted to some regions
+        if embedded.counts() == loaded.counts() {
+            for institution in embedded.institutions() {
+                for domain in [
+                    institution.domain.clone(),
+                    format!("mail.{}", institution.domain),
+                ] {
+                    assert_eq!(
+                        embedded.validate_domain(&domain),
+                        loaded.validate_domain(&domain),
+                        "{}",
+                        domain
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            loaded.validate("lee@gmail.com").unwrap_err(),
+            Error::FreeMailProvider
+        );
+        assert_eq!(
+            loaded.institution_names("stanford.edu"),
+            embedded.institution_names("stanford.edu")
+        );
+        assert_eq!(
+            loaded.suggest("lee@ugr.ess"),
+            embedded.suggest("lee@ugr.ess")
+        );
+        if embedded.counts() == loaded.counts() {
+            assert_eq!(loaded.hash(), embedded.hash());
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(
+            hash_entries(["stanford.edu", "ugr.es"].into_iter()),
+            hash_entries(["ugr.es", "stanford.edu"].into_iter())
+        );
+        let mut lists = ListHashes::embedded();
+        assert_eq!(lists.dataset_hash(), hash());
+        lists.stoplist = hash_entries(["2019.cqut.edu.cn"].into_iter());
+        assert_ne!(lists.dataset_hash(), hash());
+        assert_eq!(DatasetHash(0xab).to_string(), "00000000000000ab");
+        assert_eq!("00000000000000ab".parse(), Ok(DatasetHash(0xab)));
+    }
+
+    #[test]
+    fn test_institutions() {
+        assert_eq!(institutions().count(), counts().institutions);
+        assert!(
+            institutions().any(|institution| institution.domain == "stanford.edu"
+                && institution.names.len() == usize::from(HAS_INSTITUTION_NAMES))
+        );
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dataset = Dataset::embedded();
+        let tests: [(&str, &[&str]); 6] = [
+            ("lreilly@stanford.eud", &["lreilly@stanford.edu"]),
+            ("Lee@UGR.ess", &["lee@ugr.es"]),
+            ("lee@cs.stanfrod.edu", &["lee@cs.stanford.edu"]),
+            ("lreilly@stanford.edu", &[]),
+            ("lee@gmail.com", &[]),
+            ("not an email", &[]),
+        ];
+        for (email, suggestions) in tests {
+            let found = dataset.suggest(email);
+            assert_eq!(
+                found.iter().map(Email::as_str).collect::<Vec<_>>(),
+                suggestions,
+                "{}",
+                email
+            );
+        }
+    }
+}
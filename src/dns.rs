@@ -0,0 +1,237 @@
+//! Verification that email domains have a mail server.
+//!
+//! Domains are resolved through a [`Resolver`], so the check can run against
+//! the system resolver with the `dns` feature, or offline against an
+//! [`InMemoryResolver`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rswot::Error;
+//! use rswot::dns::{self, InMemoryResolver};
+//!
+//! let resolver = InMemoryResolver::new().with_mx("stanford.edu", "mx.stanford.edu");
+//! let validation = rswot::validate("lreilly@stanford.edu").unwrap();
+//! assert!(dns::check_mail_server(&resolver, validation).is_ok());
+//!
+//! let validation = rswot::validate("lee@ugr.es").unwrap();
+//! assert_eq!(
+//!     dns::check_mail_server(&resolver, validation),
+//!     Err(Error::NoMailServer)
+//! );
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+use crate::{Error, Result, Validation};
+
+/// DNS resolver used to find the mail servers of a domain
+///
+/// A domain that does not exist is not an error, it has no records.
+pub trait Resolver {
+    /// Exchange hosts of the MX records of a domain, empty if it has none
+    fn lookup_mx(&self, domain: &str) -> io::Result<Vec<String>>;
+
+    /// Whether a domain has an A or AAAA record
+    fn has_address(&self, domain: &str) -> io::Result<bool>;
+}
+
+impl<R: Resolver + ?Sized> Resolver for &R {
+    fn lookup_mx(&self, domain: &str) -> io::Result<Vec<String>> {
+        (**self).lookup_mx(domain)
+    }
+
+    fn has_address(&self, domain: &str) -> io::Result<bool> {
+        (**self).has_address(domain)
+    }
+}
+
+/// Whether a domain accepts mail.
+///
+/// Mail goes to the MX records of the domain, or to the domain itself if it
+/// has an address but no MX record ([RFC 5321](https://www.rfc-editor.org/rfc/rfc5321#section-5.1)).
+/// A null MX record declares that the domain accepts no mail
+/// ([RFC 7505](https://www.rfc-editor.org/rfc/rfc7505)).
+pub fn has_mail_server(resolver: &(impl Resolver + ?Sized), domain: &str) -> io::Result<bool> {
+    let exchanges = resolver.lookup_mx(domain)?;
+    if exchanges.is_empty() {
+        return resolver.has_address(domain);
+    }
+    Ok(!exchanges
+        .iter()
+        .all(|exchange| exchange.trim_end_matches('.').is_empty()))
+}
+
+/// Check that the domain of a validated email has a mail server.
+///
+/// Will return [`Error::NoMailServer`] if it has none. Resolver failures, like
+/// timeouts, are not conclusive and keep the validation.
+pub fn check_mail_server(
+    resolver: &(impl Resolver + ?Sized),
+    validation: Validation,
+) -> Result<Validation> {
+    check_domain(resolver, validation.email.domain())?;
+    Ok(validation)
+}
+
+pub(crate) fn check_domain(resolver: &(impl Resolver + ?Sized), domain: &str) -> Result<()> {
+    match has_mail_server(resolver, domain) {
+        Ok(false) => Err(Error::NoMailServer),
+        Ok(true) | Err(_) => Ok(()),
+    }
+}
+
+/// Resolver answering from records added in memory, for tests and offline use
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryResolver {
+    mx: HashMap<String, Vec<String>>,
+    addresses: HashSet<String>,
+}
+
+impl InMemoryResolver {
+    pub fn new() -> InMemoryResolver {
+        InMemoryResolver::default()
+    }
+
+    /// Add an MX record, `.` as exchange is a null MX record
+    pub fn with_mx(mut self, domain: &str, exchange: &str) -> InMemoryResolver {
+        self.mx
+            .entry(normalize(domain))
+            .or_default()
+            .push(exchange.to_string());
+        self
+    }
+
+    /// Add an A or AAAA record
+    pub fn with_address(mut self, domain: &str) -> InMemoryResolver {
+        self.addresses.insert(normalize(domain));
+        self
+    }
+}
+
+impl Resolver for InMemoryResolver {
+    fn lookup_mx(&self, domain: &str) -> io::Result<Vec<String>> {
+        Ok(self.mx.get(&normalize(domain)).cloned().unwrap_or_default())
+    }
+
+    fn has_address(&self, domain: &str) -> io::Result<bool> {
+        Ok(self.addresses.contains(&normalize(domain)))
+    }
+}
+
+fn normalize(domain: &str) -> String {
+    domain.trim_end_matches('.').to_lowercase()
+}
+
+#[cfg(feature = "dns")]
+pub use self::system::SystemResolver;
+
+#[cfg(feature = "dns")]
+mod system {
+    use std::io;
+
+    use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+    use hickory_resolver::proto::op::ResponseCode;
+
+    use super::Resolver;
+
+    /// Resolver using the system configuration, like `/etc/resolv.conf`
+    pub struct SystemResolver(hickory_resolver::Resolver);
+
+    impl SystemResolver {
+        pub fn new() -> io::Result<SystemResolver> {
+            hickory_resolver::Resolver::from_system_conf().map(SystemResolver)
+        }
+    }
+
+    impl std::fmt::Debug for SystemResolver {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("SystemResolver").finish_non_exhaustive()
+        }
+    }
+
+    impl Resolver for SystemResolver {
+        fn lookup_mx(&self, domain: &str) -> io::Result<Vec<String>> {
+            match self.0.mx_lookup(fqdn(domain)) {
+                Ok(lookup) => Ok(lookup.iter().map(|mx| mx.exchange().to_utf8()).collect()),
+                Err(error) => no_records(error).map(|()| Vec::new()),
+            }
+        }
+
+        fn has_address(&self, domain: &str) -> io::Result<bool> {
+            match self.0.lookup_ip(fqdn(domain)) {
+                Ok(lookup) => Ok(lookup.iter().next().is_some()),
+                Err(error) => no_records(error).map(|()| false),
+            }
+        }
+    }
+
+    /// Fully qualified, so search domains are not tried
+    fn fqdn(domain: &str) -> String {
+        format!("{}.", domain.trim_end_matches('.'))
+    }
+
+    /// Whether the error is a conclusive answer without records, server
+    /// failures are reported the same way by hickory
+    fn no_records(error: ResolveError) -> io::Result<()> {
+        match error.kind() {
+            ResolveErrorKind::NoRecordsFound {
+                response_code: ResponseCode::NXDomain | ResponseCode::NoError,
+                ..
+            } => Ok(()),
+            _ => Err(io::Error::other(error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingResolver;
+
+    impl Resolver for FailingResolver {
+        fn lookup_mx(&self, _domain: &str) -> io::Result<Vec<String>> {
+            Err(io::Error::from(io::ErrorKind::TimedOut))
+        }
+
+        fn has_address(&self, _domain: &str) -> io::Result<bool> {
+            Err(io::Error::from(io::ErrorKind::TimedOut))
+        }
+    }
+
+    #[test]
+    fn test_has_mail_server() {
+        let resolver = InMemoryResolver::new()
+            .with_mx("stanford.edu", "mx.stanford.edu.")
+            .with_address("ugr.es")
+            .with_mx("uct.ac.za", ".")
+            .with_address("uct.ac.za");
+        assert!(has_mail_server(&resolver, "stanford.edu").unwrap());
+        // Falls back to the address of the domain
+        assert!(has_mail_server(&resolver, "UGR.es.").unwrap());
+        // Null MX
+        assert!(!has_mail_server(&resolver, "uct.ac.za").unwrap());
+        assert!(!has_mail_server(&resolver, "strath.ac.uk").unwrap());
+        assert!(has_mail_server(&FailingResolver, "strath.ac.uk").is_err());
+    }
+
+    #[test]
+    fn test_check_mail_server() {
+        let resolver = InMemoryResolver::new().with_address("stanford.edu");
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        assert_eq!(
+            check_mail_server(&resolver, validation.clone()),
+            Ok(validation.clone())
+        );
+        assert_eq!(
+            check_mail_server(&InMemoryResolver::new(), validation.clone()),
+            Err(Error::NoMailServer)
+        );
+        assert_eq!(
+            check_mail_server(&FailingResolver, validation.clone()),
+            Ok(validation)
+        );
+    }
+}
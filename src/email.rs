@@ -1,12 +1,13 @@
 // Logic of this parsing was taken from the validator crate
 // https://github.com/Keats/validator/blob/cf51a327390de7f2abbab7a024b431784ce0be6c/validator/src/validation/email.rs
 
+use std::borrow::Cow;
 use std::fmt;
 use std::sync::LazyLock;
 
 use regex::Regex;
 
-use crate::{Error, Result, Tld};
+use crate::{Error, Result, instrument, observer};
 
 static EMAIL_USER: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9.!#$%&'*+/=?^_`{|}~-]+\z").unwrap());
@@ -17,51 +18,147 @@ static EMAIL_DOMAIN: LazyLock<Regex> = LazyLock::new(|| {
 });
 
 /// Email address
+///
+/// The address is normalized (trimmed and lowercased) and stored in a single buffer.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Email {
-    user: String,
-    domain: String,
+    address: String,
+    at: usize,
+}
+
+/// Borrowed email address
+///
+/// Same as [`Email`], but borrows the input when it is already normalized,
+/// so parsing only allocates when the address has to be lowercased or trimmed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EmailRef<'a> {
+    address: Cow<'a, str>,
+    at: usize,
 }
 
 impl Email {
     pub fn parse(email: &str) -> Result<Email> {
-        if email.is_empty() || !email.contains('@') {
-            return Err(Error::InvalidEmail);
+        EmailRef::parse(email).map(EmailRef::into_owned)
+    }
+
+    /// Local part of the address
+    pub fn user(&self) -> &str {
+        &self.address[..self.at]
+    }
+
+    /// Domain part of the address
+    pub fn domain(&self) -> &str {
+        &self.address[self.at + 1..]
+    }
+
+    /// Normalized address
+    pub fn as_str(&self) -> &str {
+        &self.address
+    }
+
+    /// Borrow this address as an [`EmailRef`]
+    pub fn as_email_ref(&self) -> EmailRef<'_> {
+        EmailRef {
+            address: Cow::Borrowed(&self.address),
+            at: self.at,
         }
+    }
+}
 
-        let parts: Vec<&str> = email.rsplitn(2, '@').collect();
-        let user_part = parts[1].trim().to_lowercase();
-        let domain_part = parts[0].trim().to_lowercase();
+impl<'a> EmailRef<'a> {
+    pub fn parse(email: &'a str) -> Result<EmailRef<'a>> {
+        let _span = instrument::parse_span(email);
+        observer::record_input(email);
+        let Some((raw_user, raw_domain)) = email.rsplit_once('@') else {
+            instrument::invalid_email("missing @");
+            return Err(Error::InvalidEmail);
+        };
+        let user_part = raw_user.trim();
+        let domain_part = raw_domain.trim();
+
+        // The input can be borrowed as is when both parts are contiguous and already lowercase
+        let contiguous = raw_user.trim_start().len() == user_part.len()
+            && raw_domain.trim_end().len() == domain_part.len();
+        let address = if contiguous && is_normalized(user_part) && is_normalized(domain_part) {
+            let start = raw_user.len() - user_part.len();
+            Cow::Borrowed(&email[start..start + user_part.len() + 1 + domain_part.len()])
+        } else {
+            let mut address = String::with_capacity(user_part.len() + 1 + domain_part.len());
+            address.extend(user_part.chars().flat_map(char::to_lowercase));
+            address.push('@');
+            address.extend(domain_part.chars().flat_map(char::to_lowercase));
+            Cow::Owned(address)
+        };
+
+        let at = address.rfind('@').unwrap();
+        let (user_part, domain_part) = (&address[..at], &address[at + 1..]);
 
         // validate the length of each part of the email, BEFORE doing the regex
         // according to RFC5321 the max length of the local part is 64 characters
         // and the max length of the domain part is 255 characters
         // https://datatracker.ietf.org/doc/html/rfc5321#section-4.5.3.1.1
         if user_part.chars().count() > 64 || domain_part.chars().count() > 255 {
+            instrument::invalid_email("too long");
             return Err(Error::InvalidEmail);
         }
 
-        if !EMAIL_USER.is_match(&user_part) {
+        if !EMAIL_USER.is_match(user_part) {
+            instrument::invalid_email("invalid local part");
             return Err(Error::InvalidEmail);
         }
 
-        if !EMAIL_DOMAIN.is_match(&domain_part) {
+        if !EMAIL_DOMAIN.is_match(domain_part) {
+            instrument::invalid_email("invalid domain");
             return Err(Error::InvalidEmail);
         }
 
-        Ok(Email {
-            user: user_part,
-            domain: domain_part,
-        })
+        instrument::parsed(&address);
+        Ok(EmailRef { address, at })
     }
 
-    pub(crate) fn tld(&self) -> Tld {
-        Tld::new_owned(self.domain_parts()[0].to_string())
+    /// Local part of the address
+    pub fn user(&self) -> &str {
+        &self.address[..self.at]
     }
 
-    pub(crate) fn domain_parts(&self) -> Vec<&str> {
-        self.domain.rsplit('.').collect()
+    /// Domain part of the address
+    pub fn domain(&self) -> &str {
+        &self.address[self.at + 1..]
+    }
+
+    /// Normalized address
+    pub fn as_str(&self) -> &str {
+        &self.address
+    }
+
+    /// Convert into an owned [`Email`], reusing the buffer if one was allocated
+    pub fn into_owned(self) -> Email {
+        Email {
+            address: self.address.into_owned(),
+            at: self.at,
+        }
+    }
+}
+
+/// Normalize (trim and lowercase) and validate a domain name
+pub(crate) fn parse_domain(domain: &str) -> Result<Cow<'_, str>> {
+    let domain = domain.trim();
+    let domain = if is_normalized(domain) {
+        Cow::Borrowed(domain)
+    } else {
+        Cow::Owned(domain.to_lowercase())
+    };
+
+    if domain.chars().count() > 255 || !EMAIL_DOMAIN.is_match(&domain) {
+        return Err(Error::InvalidEmail);
     }
+
+    Ok(domain)
+}
+
+fn is_normalized(part: &str) -> bool {
+    part.bytes()
+        .all(|b| b.is_ascii() && !b.is_ascii_uppercase())
 }
 
 impl TryFrom<String> for Email {
@@ -78,9 +175,38 @@ impl TryFrom<&str> for Email {
     }
 }
 
+impl From<EmailRef<'_>> for Email {
+    fn from(value: EmailRef<'_>) -> Email {
+        value.into_owned()
+    }
+}
+
+impl<'a> TryFrom<&'a str> for EmailRef<'a> {
+    type Error = Error;
+    fn try_from(value: &'a str) -> Result<EmailRef<'a>> {
+        EmailRef::parse(value)
+    }
+}
+
 impl fmt::Display for Email {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}@{}", self.user, self.domain)
+        f.write_str(&self.address)
+    }
+}
+
+impl fmt::Display for EmailRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.address)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Email {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.address)
     }
 }
 
@@ -91,9 +217,33 @@ mod tests {
     #[test]
     fn test_parse_email() {
         let email = Email::parse("orhanbalci@ku.edu.tr").unwrap();
-        assert_eq!(email.domain, "ku.edu.tr");
-        assert_eq!(email.user, "orhanbalci");
-        assert_eq!(email.domain_parts(), vec!["tr", "edu", "ku"]);
-        assert_eq!(email.tld(), Tld::new("tr"));
+        assert_eq!(email.domain(), "ku.edu.tr");
+        assert_eq!(email.user(), "orhanbalci");
+        assert_eq!(email.to_string(), "orhanbalci@ku.edu.tr");
+    }
+
+    #[test]
+    fn test_parse_domain() {
+        assert_eq!(parse_domain(" KU.edu.tr").unwrap(), "ku.edu.tr");
+        assert_eq!(parse_domain("ku..tr"), Err(Error::InvalidEmail));
+    }
+
+    #[test]
+    fn test_parse_email_ref() {
+        let email = EmailRef::parse("orhanbalci@ku.edu.tr").unwrap();
+        assert!(matches!(email.address, Cow::Borrowed(_)));
+
+        let email = EmailRef::parse(" orhanbalci@ku.edu.tr ").unwrap();
+        assert!(matches!(email.address, Cow::Borrowed(_)));
+        assert_eq!(email.as_str(), "orhanbalci@ku.edu.tr");
+
+        let email = EmailRef::parse("OrhanBalci @ KU.edu.tr").unwrap();
+        assert!(matches!(email.address, Cow::Owned(_)));
+        assert_eq!(email.user(), "orhanbalci");
+        assert_eq!(email.domain(), "ku.edu.tr");
+        assert_eq!(
+            email.into_owned(),
+            Email::parse("orhanbalci@ku.edu.tr").unwrap()
+        );
     }
 }
@@ -1,19 +1,183 @@
+use std::convert::Infallible;
+
 use thiserror::Error;
 
 /// Error type for validation
-#[derive(Error, Debug, PartialEq, Eq, Hash)]
+///
+/// Serialized as its [`code`](Error::code) with the `serde` feature.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Error {
     #[error("Invalid email")]
     InvalidEmail,
 
+    /// Any of the abuse categories, when they are combined with [`AbuseMode::Combined`]
     #[error("Email is from a known abusive domain")]
     Abuse,
 
+    #[error("Email is from a free mail provider")]
+    FreeMailProvider,
+
+    #[error("Email is from a disposable mail provider")]
+    DisposableProvider,
+
+    #[error("Email is from an academic domain known for abuse")]
+    AbusedAcademicDomain,
+
     #[error("Email is marked as stop")]
     Stop,
 
     #[error("Email is not academic")]
     NotAcademic,
+
+    #[error("Email domain has no mail server")]
+    NoMailServer,
+
+    /// Domain is not academic but looks like the domain of an institution,
+    /// see [`Validator::with_impersonation_check`](crate::Validator::with_impersonation_check)
+    #[error("Email domain looks like {target}")]
+    SuspectedImpersonation {
+        /// Domain of the institution
+        target: String,
+    },
+
+    /// Domain is not academic but embeds the domain of an institution or an
+    /// academic suffix in its inner labels, like `stanford.edu.com`
+    #[error("Email domain embeds {target}")]
+    Deceptive {
+        /// Domain of the institution, or academic suffix
+        target: String,
+    },
+
+    /// Internationalized domain label mixes scripts or uses a script that is
+    /// not allowed, see [`idn::check_domain`](crate::idn::check_domain)
+    #[error("Email domain mixes scripts or uses a script that is not allowed")]
+    MixedScript,
+
+    /// Internationalized domain looks the same as an academic domain, see
+    /// [`idn::check_domain`](crate::idn::check_domain)
+    #[error("Email domain is confusable with {target}")]
+    Confusable {
+        /// Domain of the institution, or academic suffix
+        target: String,
+    },
+
+    /// Email is a role account like `admin@`, see
+    /// [`Validator::with_role_account_check`](crate::Validator::with_role_account_check)
+    #[error("Email is a role account, not an individual")]
+    RoleAccount,
+
+    /// Domain is a cohort subdomain of an institution, like `2011.cqut.edu.cn`,
+    /// that is not accepted, see [`CohortPolicy`](crate::CohortPolicy)
+    #[error("Email domain is for the cohort of {year}")]
+    Cohort {
+        /// Year of the cohort
+        year: u16,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Stable snake case identifier of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::InvalidEmail => "invalid_email",
+            Error::Abuse => "abuse",
+            Error::FreeMailProvider => "free_mail_provider",
+            Error::DisposableProvider => "disposable_provider",
+            Error::AbusedAcademicDomain => "abused_academic_domain",
+            Error::Stop => "stop",
+            Error::NotAcademic => "not_academic",
+            Error::NoMailServer => "no_mail_server",
+            Error::SuspectedImpersonation { .. } => "suspected_impersonation",
+            Error::Deceptive { .. } => "deceptive",
+            Error::MixedScript => "mixed_script",
+            Error::Confusable { .. } => "confusable",
+            Error::RoleAccount => "role_account",
+            Error::Cohort { .. } => "cohort",
+        }
+    }
+
+    /// Whether the email was rejected for being from an abusive domain,
+    /// whatever its category
+    pub fn is_abuse(&self) -> bool {
+        matches!(
+            self,
+            Error::Abuse
+                | Error::FreeMailProvider
+                | Error::DisposableProvider
+                | Error::AbusedAcademicDomain
+        )
+    }
+}
+
+/// How the abuse categories are reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AbuseMode {
+    /// Report [`Error::FreeMailProvider`], [`Error::DisposableProvider`] and
+    /// [`Error::AbusedAcademicDomain`]
+    #[default]
+    Distinct,
+    /// Report all of them as [`Error::Abuse`], like before they were distinct
+    Combined,
+}
+
+impl AbuseMode {
+    pub(crate) fn apply(self, error: Error) -> Error {
+        match self {
+            AbuseMode::Combined if error.is_abuse() => Error::Abuse,
+            _ => error,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Error {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl From<Infallible> for Error {
+    fn from(value: Infallible) -> Error {
+        match value {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abuse_mode() {
+        for error in [Error::FreeMailProvider, Error::AbusedAcademicDomain] {
+            assert!(error.is_abuse());
+            assert_eq!(AbuseMode::Distinct.apply(error.clone()), error);
+            assert_eq!(AbuseMode::Combined.apply(error), Error::Abuse);
+        }
+        assert!(!Error::NotAcademic.is_abuse());
+        assert_eq!(AbuseMode::Combined.apply(Error::Stop), Error::Stop);
+    }
+
+    #[test]
+    fn test_code() {
+        assert_eq!(Error::FreeMailProvider.code(), "free_mail_provider");
+        let error = Error::SuspectedImpersonation {
+            target: "stanford.edu".to_string(),
+        };
+        assert_eq!(error.code(), "suspected_impersonation");
+        assert_eq!(error.to_string(), "Email domain looks like stanford.edu");
+        let error = Error::Deceptive {
+            target: "stanford.edu".to_string(),
+        };
+        assert_eq!(error.code(), "deceptive");
+        assert_eq!(error.to_string(), "Email domain embeds stanford.edu");
+        assert_eq!(Error::RoleAccount.code(), "role_account");
+        let error = Error::Cohort { year: 2011 };
+        assert_eq!(error.code(), "cohort");
+        assert_eq!(error.to_string(), "Email domain is for the cohort of 2011");
+    }
+}
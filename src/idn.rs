@@ -0,0 +1,203 @@
+//! Detection of spoofed internationalized domain names.
+//!
+//! Internationalized labels, written as `xn--` A-labels, are decoded and checked
+//! following [UTS #39](https://www.unicode.org/reports/tr39/):
+//!
+//! - a label must be single-script, like `hаrvard` with a Cyrillic `а` is not,
+//!   and only use the scripts allowed by a [`ScriptPolicy`];
+//! - the domain must not have the same skeleton as a domain or academic suffix
+//!   of the dataset, like `ԝіѕс.edu` written in Cyrillic.
+//!
+//! ASCII domains are left to the dataset and to
+//! [`Validator::with_impersonation_check`](crate::Validator::with_impersonation_check).
+//!
+//! # Examples
+//!
+//! ```
+//! use rswot::Error;
+//! use rswot::dataset::Dataset;
+//! use rswot::idn::{self, ScriptPolicy};
+//!
+//! let dataset = Dataset::embedded();
+//! let policy = ScriptPolicy::default();
+//! // "hаrvard.edu" with a Cyrillic "а"
+//! assert_eq!(
+//!     idn::check_domain(&dataset, "xn--hrvard-3nf.edu", &policy),
+//!     Err(Error::MixedScript)
+//! );
+//! assert!(idn::check_domain(&dataset, "stanford.edu", &policy).is_ok());
+//! ```
+
+use std::collections::HashSet;
+
+pub use unicode_script::Script;
+use unicode_script::UnicodeScript;
+use unicode_security::MixedScript;
+
+use crate::dataset::Dataset;
+use crate::{Error, Result};
+
+/// Scripts that internationalized labels may use
+///
+/// By default, any script [recommended](https://www.unicode.org/reports/tr31/#Table_Recommended_Scripts)
+/// for identifiers is allowed. Characters common to all scripts, like digits and
+/// hyphens, are always allowed.
+///
+/// # Examples
+///
+/// ```
+/// use rswot::idn::{Script, ScriptPolicy};
+///
+/// let policy = ScriptPolicy::allow([Script::Latin, Script::Cyrillic]);
+/// assert!(policy.is_allowed(Script::Cyrillic));
+/// assert!(!policy.is_allowed(Script::Greek));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptPolicy {
+    /// Allowed scripts, the recommended ones if `None`
+    allowed: Option<HashSet<Script>>,
+}
+
+impl ScriptPolicy {
+    /// Only allow these scripts
+    pub fn allow(scripts: impl IntoIterator<Item = Script>) -> ScriptPolicy {
+        ScriptPolicy {
+            allowed: Some(scripts.into_iter().collect()),
+        }
+    }
+
+    /// Whether labels may use a script
+    pub fn is_allowed(&self, script: Script) -> bool {
+        match script {
+            Script::Common | Script::Inherited => true,
+            script => match &self.allowed {
+                Some(allowed) => allowed.contains(&script),
+                None => script.is_recommended(),
+            },
+        }
+    }
+}
+
+/// Check the internationalized labels of a normalized domain.
+///
+/// Will return [`Error::MixedScript`] if a label mixes scripts or uses a script
+/// that the policy does not allow, [`Error::Confusable`] if the domain looks
+/// the same as an institution domain or academic suffix of the dataset, and
+/// [`Error::InvalidEmail`] if a label is not valid punycode.
+pub fn check_domain(dataset: &Dataset, domain: &str, policy: &ScriptPolicy) -> Result<()> {
+    if !domain.split('.').any(|label| label.starts_with("xn--")) {
+        return Ok(());
+    }
+    let (unicode, decoded) = idna::domain_to_unicode(domain);
+    if decoded.is_err() {
+        return Err(Error::InvalidEmail);
+    }
+
+    for label in unicode.split('.').filter(|label| !label.is_ascii()) {
+        if !label.is_single_script() || !label.chars().all(|c| policy.is_allowed(c.script())) {
+            return Err(Error::MixedScript);
+        }
+    }
+
+    // Suffixes of the domain, from the longest
+    let labels: Vec<&str> = unicode.split('.').collect();
+    for start in 0..labels.len() {
+        let suffix = labels[start..].join(".");
+        if suffix.is_ascii() {
+            break;
+        }
+        if let Some(target) = dataset.index().with_unicode_skeleton_of(&suffix) {
+            return Err(Error::Confusable {
+                target: target.domain.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Skeleton of a domain, which is the same for domains that look alike
+pub(crate) fn skeleton(domain: &str) -> String {
+    unicode_security::skeleton(domain).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_domain() {
+        let dataset = Dataset::embedded();
+        let policy = ScriptPolicy::default();
+        let tests = [
+            ("stanford.edu", Ok(())),
+            ("leerilly.net", Ok(())),
+            // "münchen.de"
+            ("xn--mnchen-3ya.de", Ok(())),
+            // "мгу.рф"
+            ("xn--c1arw.xn--p1ai", Ok(())),
+            // "hаrvard.edu" with a Cyrillic "а"
+            ("xn--hrvard-3nf.edu", Err(Error::MixedScript)),
+            // "һаrvаrd.edu" with Cyrillic letters and a Latin "r"
+            ("xn--rvrd-43dc38g.edu", Err(Error::MixedScript)),
+            // "ԝіѕс.edu" is all Cyrillic, and looks like wisc.edu
+            (
+                "xn--q1a3bc90f.edu",
+                Err(Error::Confusable {
+                    target: "wisc.edu".to_string(),
+                }),
+            ),
+            ("xn--zzzz.edu", Err(Error::InvalidEmail)),
+        ];
+        for (domain, result) in tests {
+            assert_eq!(
+                check_domain(&dataset, domain, &policy),
+                result,
+                "{}",
+                domain
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_domain_with_loaded_dataset() {
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("rswot-idn-{}", std::process::id()));
+        fs::create_dir_all(dir.join("edu")).unwrap();
+        for file in ["tlds.txt", "stoplist.txt", "abused.txt"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        fs::write(dir.join("edu").join("cxe.txt"), "CXE University\n").unwrap();
+        let dataset = Dataset::load(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let policy = ScriptPolicy::default();
+        // "ԝіѕс.edu" looks like wisc.edu, which this dataset doesn't have
+        assert_eq!(check_domain(&dataset, "xn--q1a3bc90f.edu", &policy), Ok(()));
+        // "схе.edu" in Cyrillic looks like cxe.edu, which only this dataset has
+        let domain = idna::domain_to_ascii("\u{441}\u{445}\u{435}.edu").unwrap();
+        let confusable = Err(Error::Confusable {
+            target: "cxe.edu".to_string(),
+        });
+        assert_eq!(check_domain(&dataset, &domain, &policy), confusable);
+        assert_ne!(
+            check_domain(&Dataset::embedded(), &domain, &policy),
+            confusable
+        );
+    }
+
+    #[test]
+    fn test_script_policy() {
+        // "мгу.рф"
+        let domain = "xn--c1arw.xn--p1ai";
+        let dataset = Dataset::embedded();
+        let policy = ScriptPolicy::allow([Script::Latin]);
+        assert_eq!(
+            check_domain(&dataset, domain, &policy),
+            Err(Error::MixedScript)
+        );
+        let policy = ScriptPolicy::allow([Script::Latin, Script::Cyrillic]);
+        assert_eq!(check_domain(&dataset, domain, &policy), Ok(()));
+        assert!(policy.is_allowed(Script::Common));
+    }
+}
@@ -0,0 +1,147 @@
+//! Detection of domains that impersonate institutions.
+//!
+//! A domain that is not academic is compared with the institution domains of the
+//! dataset, looking for:
+//!
+//! - typos, like `stanfrod.edu`, within an edit distance that grows with the
+//!   length of the institution name;
+//! - homoglyphs, like `stanf0rd.edu`, compared through a skeleton where
+//!   confusable characters are the same;
+//! - institution domains embedded in another one, like `stanfrod.edu.co` or
+//!   `harvard-edu.org` where the hyphen stands for a dot;
+//! - transposed labels, like `edu.stanford.com`.
+
+use crate::index::{Costs, DomainIndex, distance};
+
+/// Institution domain of the index that a normalized domain looks like, the
+/// closest one if there are several
+pub(crate) fn find<'a>(index: &'a DomainIndex, domain: &str) -> Option<&'a str> {
+    let mut best: Option<(u32, &str)> = None;
+    for candidate in candidates(domain) {
+        let candidate_skeleton = skeleton(&candidate);
+
+        // Same labels in another order
+        if let Some(target) = index.with_labels_of(&candidate_skeleton) {
+            if target.skeleton != candidate_skeleton {
+                best = closest(best, (1, &target.domain));
+            }
+        }
+
+        for target in index.with_top_label_of(&candidate_skeleton) {
+            let max = max_distance(&target.domain);
+            if target.skeleton.len().abs_diff(candidate_skeleton.len()) > max as usize {
+                continue;
+            }
+            let Some(edits) = distance(&candidate_skeleton, &target.skeleton, Costs::Uniform, max)
+            else {
+                continue;
+            };
+            if edits == 0 && target.domain == domain {
+                continue;
+            }
+            best = closest(best, (edits, &target.domain));
+        }
+    }
+    best.map(|(_, target)| target)
+}
+
+/// Maximum edit distance between the skeletons of an institution domain and of
+/// a lookalike, by length of the institution name
+fn max_distance(domain: &str) -> u32 {
+    match domain.split('.').next().unwrap_or(domain).len() {
+        0..5 => 0,
+        5..13 => 1,
+        _ => 2,
+    }
+}
+
+fn closest<'a>(best: Option<(u32, &'a str)>, other: (u32, &'a str)) -> Option<(u32, &'a str)> {
+    match best {
+        Some(best) if best <= other => Some(best),
+        _ => Some(other),
+    }
+}
+
+/// Domains of at least two consecutive labels of `domain`, and of `domain` with
+/// hyphens read as dots
+fn candidates(domain: &str) -> Vec<String> {
+    let mut variants = vec![domain.to_string()];
+    if domain.contains('-') {
+        variants.push(domain.replace('-', "."));
+    }
+
+    let mut candidates = Vec::new();
+    for variant in &variants {
+        let labels: Vec<&str> = variant.split('.').filter(|l| !l.is_empty()).collect();
+        for start in 0..labels.len() {
+            for end in start + 2..=labels.len() {
+                candidates.push(labels[start..end].join("."));
+            }
+        }
+    }
+    candidates
+}
+
+/// Domain where characters that look alike are replaced by the same one
+pub(crate) fn skeleton(domain: &str) -> String {
+    domain
+        .replace("rn", "m")
+        .replace("vv", "w")
+        .replace("cl", "d")
+        .chars()
+        .map(|c| match c {
+            '0' => 'o',
+            '1' | 'i' => 'l',
+            '3' => 'e',
+            '4' => 'a',
+            '5' => 's',
+            '7' => 't',
+            '8' => 'b',
+            '9' | 'q' => 'g',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skeleton() {
+        assert_eq!(skeleton("stanf0rd.edu"), skeleton("stanford.edu"));
+        assert_eq!(skeleton("cornel1.edu"), skeleton("cornell.edu"));
+        assert_eq!(skeleton("com.edu"), skeleton("corn.edu"));
+    }
+
+    #[test]
+    fn test_find() {
+        let index = DomainIndex::new(
+            ["stanford.edu", "harvard.edu", "mit.edu", "strath.ac.uk"]
+                .into_iter()
+                .map(String::from),
+            ["ac.za"].into_iter().map(String::from),
+        );
+        let tests = [
+            ("stanfrod.edu", Some("stanford.edu")),
+            ("stanfrod.edu.co", Some("stanford.edu")),
+            ("stanf0rd.edu", Some("stanford.edu")),
+            ("mail.standford.edu", Some("stanford.edu")),
+            ("harvard-edu.org", Some("harvard.edu")),
+            ("edu.stanford.com", Some("stanford.edu")),
+            ("strath.ac.uk.com", Some("strath.ac.uk")),
+            ("strathh.ac.uk", Some("strath.ac.uk")),
+            ("m1t.edu", Some("mit.edu")),
+            // Short names only match through homoglyphs
+            ("mat.edu", None),
+            ("stanford.edu", None),
+            ("stanford.com", None),
+            ("leerilly.net", None),
+            // Academic suffixes are not institutions
+            ("za.ac.com", None),
+        ];
+        for (domain, target) in tests {
+            assert_eq!(find(&index, domain), target, "{}", domain);
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Index of the academic domains of a dataset, and the edit distance used to
+//! search it.
+//!
+//! The index is built once per dataset, on first use, and shared by the
+//! searches for lookalikes of institutions, for corrections of typos and for
+//! confusable internationalized domains.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+#[cfg(feature = "idn")]
+use crate::idn;
+use crate::{data, impersonation};
+
+/// Index of the embedded dataset
+static EMBEDDED: LazyLock<DomainIndex> = LazyLock::new(|| {
+    DomainIndex::new(
+        data::institutions().map(|(domain, _)| domain),
+        data::academic_suffixes(),
+    )
+});
+
+pub(crate) fn embedded() -> &'static DomainIndex {
+    &EMBEDDED
+}
+
+/// Institution domains and academic suffixes of a dataset, indexed for
+/// searches of domains that look like them
+#[derive(Debug)]
+pub(crate) struct DomainIndex {
+    /// Institutions sorted by domain, then academic suffixes
+    domains: Vec<Known>,
+    /// Domains by number of labels and length
+    by_length: HashMap<(usize, usize), Vec<usize>>,
+    /// Institutions by number of labels and skeleton of the top-level label
+    by_top_label: HashMap<(usize, String), Vec<usize>>,
+    /// Institutions by the sorted labels of their skeleton, the first domain
+    /// if several have the same
+    by_labels: HashMap<String, usize>,
+    /// Domains by their Unicode skeleton, see [`idn::skeleton`], the first
+    /// domain if several have the same
+    #[cfg(feature = "idn")]
+    by_unicode_skeleton: HashMap<String, usize>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Known {
+    pub(crate) domain: String,
+    /// Academic suffixes need at least one label before them
+    pub(crate) is_suffix: bool,
+    /// Domain where characters that look alike are the same, see
+    /// [`impersonation::skeleton`]
+    pub(crate) skeleton: String,
+}
+
+impl DomainIndex {
+    pub(crate) fn new(
+        institutions: impl Iterator<Item = String>,
+        suffixes: impl Iterator<Item = String>,
+    ) -> DomainIndex {
+        let mut institutions: Vec<String> = institutions.collect();
+        // Ties are broken by domain, whatever the order of the dataset
+        institutions.sort_unstable();
+        let institutions = institutions.into_iter().map(|domain| (domain, false));
+        let suffixes = suffixes.map(|domain| (domain, true));
+
+        let mut index = DomainIndex {
+            domains: Vec::new(),
+            by_length: HashMap::new(),
+            by_top_label: HashMap::new(),
+            by_labels: HashMap::new(),
+            #[cfg(feature = "idn")]
+            by_unicode_skeleton: HashMap::new(),
+        };
+        for (i, (domain, is_suffix)) in institutions.chain(suffixes).enumerate() {
+            let skeleton = impersonation::skeleton(&domain);
+            index
+                .by_length
+                .entry((domain.split('.').count(), domain.len()))
+                .or_default()
+                .push(i);
+            if !is_suffix {
+                index
+                    .by_top_label
+                    .entry(top_label_key(&skeleton))
+                    .or_default()
+                    .push(i);
+                index.by_labels.entry(sorted_labels(&skeleton)).or_insert(i);
+            }
+            #[cfg(feature = "idn")]
+            index
+                .by_unicode_skeleton
+                .entry(idn::skeleton(&domain))
+                .or_insert(i);
+            index.domains.push(Known {
+                domain,
+                is_suffix,
+                skeleton,
+            });
+        }
+        index
+    }
+
+    /// Domains with this number of labels and length
+    pub(crate) fn with_length(&self, labels: usize, len: usize) -> impl Iterator<Item = &Known> {
+        self.entries(self.by_length.get(&(labels, len)))
+    }
+
+    /// Institutions with the same number of labels and top-level label as a
+    /// skeleton
+    pub(crate) fn with_top_label_of(&self, skeleton: &str) -> impl Iterator<Item = &Known> {
+        self.entries(self.by_top_label.get(&top_label_key(skeleton)))
+    }
+
+    /// Institution with the same labels as a skeleton, in any order
+    pub(crate) fn with_labels_of(&self, skeleton: &str) -> Option<&Known> {
+        let i = self.by_labels.get(&sorted_labels(skeleton))?;
+        Some(&self.domains[*i])
+    }
+
+    /// Domain with the same Unicode skeleton as a domain, see [`idn::skeleton`]
+    #[cfg(feature = "idn")]
+    pub(crate) fn with_unicode_skeleton_of(&self, domain: &str) -> Option<&Known> {
+        let i = self.by_unicode_skeleton.get(&idn::skeleton(domain))?;
+        Some(&self.domains[*i])
+    }
+
+    fn entries<'a>(&'a self, entries: Option<&'a Vec<usize>>) -> impl Iterator<Item = &'a Known> {
+        entries.into_iter().flatten().map(|i| &self.domains[*i])
+    }
+}
+
+fn top_label_key(skeleton: &str) -> (usize, String) {
+    let top_label = skeleton.rsplit('.').next().unwrap_or(skeleton);
+    (skeleton.split('.').count(), top_label.to_string())
+}
+
+fn sorted_labels(domain: &str) -> String {
+    let mut labels: Vec<&str> = domain.split('.').collect();
+    labels.sort_unstable();
+    labels.join(".")
+}
+
+/// Costs of the edits of [`distance`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Costs {
+    /// Every edit costs 1
+    Uniform,
+    /// Typing mistakes cost 1 and other edits 2
+    ///
+    /// Typing mistakes are substituting a key next to the intended one,
+    /// inserting or deleting a repeated character and transposing adjacent
+    /// characters.
+    Typing,
+}
+
+impl Costs {
+    fn substitution(self, a: u8, b: u8) -> u32 {
+        match self {
+            _ if a == b => 0,
+            Costs::Uniform => 1,
+            Costs::Typing if adjacent_keys(a, b) => 1,
+            Costs::Typing => 2,
+        }
+    }
+
+    /// Cost of inserting or deleting the `i`th character of `s`, from 1
+    fn insertion(self, s: &[u8], i: usize) -> u32 {
+        match self {
+            Costs::Uniform => 1,
+            Costs::Typing if i > 1 && s[i - 1] == s[i - 2] => 1,
+            Costs::Typing => 2,
+        }
+    }
+}
+
+/// Optimal string alignment distance, where transposing adjacent characters
+/// costs 1, or `None` if it is over `max`
+pub(crate) fn distance(a: &str, b: &str, costs: Costs, max: u32) -> Option<u32> {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+
+    let mut previous2 = vec![0; b.len() + 1];
+    let mut previous = vec![0; b.len() + 1];
+    for j in 1..=b.len() {
+        previous[j] = previous[j - 1] + costs.insertion(b, j);
+    }
+    let mut current = vec![0; b.len() + 1];
+    let mut previous_min = 0;
+
+    for i in 1..=a.len() {
+        current[0] = previous[0] + costs.insertion(a, i);
+        let mut row_min = current[0];
+        for j in 1..=b.len() {
+            current[j] = (previous[j] + costs.insertion(a, i))
+                .min(current[j - 1] + costs.insertion(b, j))
+                .min(previous[j - 1] + costs.substitution(a[i - 1], b[j - 1]));
+            if i > 1
+                && j > 1
+                && a[i - 1] != a[i - 2]
+                && a[i - 1] == b[j - 2]
+                && a[i - 2] == b[j - 1]
+            {
+                current[j] = current[j].min(previous2[j - 2] + 1);
+            }
+            row_min = row_min.min(current[j]);
+        }
+        // A transposition can go back under `max` from the row before the previous one
+        if row_min > max && previous_min > max {
+            return None;
+        }
+        previous_min = row_min;
+        std::mem::swap(&mut previous2, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Some(previous[b.len()]).filter(|distance| *distance <= max)
+}
+
+/// Rows of a QWERTY keyboard, with their offset in quarters of a key
+const KEYBOARD: [(&[u8], i32); 4] = [
+    (b"1234567890-", 0),
+    (b"qwertyuiop", 2),
+    (b"asdfghjkl", 3),
+    (b"zxcvbnm,.", 5),
+];
+
+/// Row and horizontal position in quarters of a key, by ASCII character
+const KEY_POSITIONS: [Option<(i32, i32)>; 128] = key_positions();
+
+const fn key_positions() -> [Option<(i32, i32)>; 128] {
+    let mut positions = [None; 128];
+    let mut row = 0;
+    while row < KEYBOARD.len() {
+        let (keys, offset) = KEYBOARD[row];
+        let mut column = 0;
+        while column < keys.len() {
+            positions[keys[column] as usize] = Some((row as i32, offset + 4 * column as i32));
+            column += 1;
+        }
+        row += 1;
+    }
+    positions
+}
+
+fn key_position(c: u8) -> Option<(i32, i32)> {
+    KEY_POSITIONS.get(usize::from(c)).copied().flatten()
+}
+
+/// Whether two keys touch on a QWERTY keyboard
+fn adjacent_keys(a: u8, b: u8) -> bool {
+    let (Some((a_row, a_x)), Some((b_row, b_x))) = (key_position(a), key_position(b)) else {
+        return false;
+    };
+    match a_row.abs_diff(b_row) {
+        0 => a_x.abs_diff(b_x) == 4,
+        1 => a_x.abs_diff(b_x) <= 3,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjacent_keys() {
+        assert!(adjacent_keys(b'z', b'x'));
+        assert!(adjacent_keys(b'z', b'a'));
+        assert!(adjacent_keys(b'q', b'1'));
+        assert!(adjacent_keys(b'j', b'm'));
+        assert!(!adjacent_keys(b'q', b's'));
+        assert!(!adjacent_keys(b'a', b'd'));
+        assert!(!adjacent_keys(b'a', b'a'));
+    }
+
+    #[test]
+    fn test_distance() {
+        let uniform = |a, b| distance(a, b, Costs::Uniform, 2);
+        assert_eq!(uniform("stanford", "stanford"), Some(0));
+        assert_eq!(uniform("stanfrod", "stanford"), Some(1));
+        assert_eq!(uniform("standford", "stanford"), Some(1));
+        assert_eq!(uniform("stnfrd", "stanford"), Some(2));
+        assert_eq!(uniform("yale", "stanford"), None);
+
+        let typing = |a, b, max| distance(a, b, Costs::Typing, max);
+        assert_eq!(typing("stanford.edu", "stanford.edu", 3), Some(0));
+        assert_eq!(typing("stanford.eud", "stanford.edu", 3), Some(1));
+        assert_eq!(typing("ugr.ess", "ugr.es", 3), Some(1));
+        assert_eq!(typing("mit.eud", "mit.edu", 1), Some(1));
+        assert_eq!(typing("ac.xa", "ac.za", 3), Some(1));
+        assert_eq!(typing("ac.ma", "ac.za", 3), Some(2));
+        assert_eq!(typing("stanfod.edu", "stanford.edu", 3), Some(2));
+        assert_eq!(typing("stanford.com", "stanford.edu", 3), None);
+    }
+
+    #[test]
+    fn test_domain_index() {
+        let index = DomainIndex::new(
+            ["stanford.edu", "harvard.edu"]
+                .into_iter()
+                .map(String::from),
+            ["ac.za"].into_iter().map(String::from),
+        );
+        let domains = |known: Vec<&Known>| -> Vec<String> {
+            known.into_iter().map(|k| k.domain.clone()).collect()
+        };
+        assert_eq!(
+            domains(index.with_length(2, 12).collect()),
+            ["stanford.edu"]
+        );
+        assert_eq!(domains(index.with_length(2, 5).collect()), ["ac.za"]);
+        assert_eq!(
+            domains(index.with_top_label_of("yale.edu").collect()),
+            ["harvard.edu", "stanford.edu"]
+        );
+        // Suffixes are not institutions
+        assert_eq!(index.with_top_label_of("uct.za").count(), 0);
+        assert_eq!(
+            index.with_labels_of("edu.stanford").unwrap().domain,
+            "stanford.edu"
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! Spans and events of the `tracing` feature.
+//!
+//! Every helper is a no-op without the feature, so call sites don't need to be
+//! gated. Emails and domains are written in fields as set by [`set_redaction`].
+
+#[cfg(feature = "tracing")]
+use std::fmt;
+#[cfg(feature = "tracing")]
+use std::sync::RwLock;
+#[cfg(feature = "tracing")]
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[cfg(feature = "tracing")]
+use hmac::{Hmac, Mac};
+#[cfg(feature = "tracing")]
+use sha2::Sha256;
+#[cfg(feature = "tracing")]
+use tracing::span::EnteredSpan;
+
+use crate::{Email, Result, Validation};
+
+#[cfg(feature = "tracing")]
+type HmacSha256 = Hmac<Sha256>;
+
+/// How emails and domains are written in the fields of the spans and events of
+/// the `tracing` feature
+///
+/// Local parts are redacted by default, since they identify people.
+///
+/// # Examples
+///
+/// ```
+/// use rswot::Redaction;
+///
+/// rswot::set_redaction(Redaction::All);
+/// assert_eq!(rswot::redaction(), Redaction::All);
+/// ```
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+pub enum Redaction {
+    /// Write emails and domains as is, only for debugging
+    None,
+    /// Replace local parts of emails with `<redacted>`, keep domains
+    #[default]
+    LocalPart,
+    /// Replace emails and domains with their HMAC-SHA256, keyed with
+    /// [`set_redaction_key`], to correlate them without writing them
+    ///
+    /// They are replaced with `<redacted>` until a key is set.
+    Hash,
+    /// Replace emails and domains with `<redacted>`
+    All,
+}
+
+#[cfg(feature = "tracing")]
+static REDACTION: AtomicU8 = AtomicU8::new(Redaction::LocalPart as u8);
+
+/// Set how emails and domains are written in the fields of spans and events,
+/// for the whole process
+#[cfg(feature = "tracing")]
+pub fn set_redaction(redaction: Redaction) {
+    REDACTION.store(redaction as u8, Ordering::Relaxed);
+}
+
+/// Key of [`Redaction::Hash`], already keyed so hashing only clones it
+#[cfg(feature = "tracing")]
+static REDACTION_KEY: RwLock<Option<HmacSha256>> = RwLock::new(None);
+
+/// Number of bytes of the HMAC written for [`Redaction::Hash`]
+#[cfg(feature = "tracing")]
+const HASH_LEN: usize = 16;
+
+/// Set the key of [`Redaction::Hash`] for the whole process, which should be at
+/// least 32 random bytes
+///
+/// Hashes can only be correlated while the key is the same, and the key must be
+/// kept as secret as the emails since anyone with it can recover them from a
+/// dictionary.
+///
+/// # Examples
+///
+/// ```
+/// use rswot::Redaction;
+///
+/// rswot::set_redaction_key(b"a secret of at least 32 bytes....");
+/// rswot::set_redaction(Redaction::Hash);
+/// ```
+#[cfg(feature = "tracing")]
+pub fn set_redaction_key(key: &[u8]) {
+    let mac = HmacSha256::new_from_slice(key).expect("HMAC takes keys of any size");
+    *REDACTION_KEY.write().unwrap() = Some(mac);
+}
+
+/// How emails and domains are written in the fields of spans and events
+#[cfg(feature = "tracing")]
+pub fn redaction() -> Redaction {
+    match REDACTION.load(Ordering::Relaxed) {
+        0 => Redaction::None,
+        1 => Redaction::LocalPart,
+        2 => Redaction::Hash,
+        _ => Redaction::All,
+    }
+}
+
+#[cfg(feature = "tracing")]
+const REDACTED: &str = "<redacted>";
+
+/// Email, or any input parsed as one, formatted with the redaction in place
+#[cfg(feature = "tracing")]
+struct RedactedEmail<'a>(&'a str);
+
+#[cfg(feature = "tracing")]
+impl fmt::Display for RedactedEmail<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match redaction() {
+            Redaction::None => f.write_str(self.0),
+            Redaction::LocalPart => match self.0.rsplit_once('@') {
+                Some((_, domain)) => write!(f, "{}@{}", REDACTED, domain),
+                None => f.write_str(REDACTED),
+            },
+            Redaction::Hash => write_hash(f, self.0),
+            Redaction::All => f.write_str(REDACTED),
+        }
+    }
+}
+
+/// Domain formatted with the redaction in place
+#[cfg(feature = "tracing")]
+struct RedactedDomain<'a>(&'a str);
+
+#[cfg(feature = "tracing")]
+impl fmt::Display for RedactedDomain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match redaction() {
+            Redaction::None | Redaction::LocalPart => f.write_str(self.0),
+            Redaction::Hash => write_hash(f, self.0),
+            Redaction::All => f.write_str(REDACTED),
+        }
+    }
+}
+
+/// Write the keyed hash of a value in hexadecimal, or `<redacted>` without a key
+#[cfg(feature = "tracing")]
+fn write_hash(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    let Some(mut mac) = REDACTION_KEY.read().unwrap().clone() else {
+        return f.write_str(REDACTED);
+    };
+    mac.update(value.as_bytes());
+    for byte in &mac.finalize().into_bytes()[..HASH_LEN] {
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+/// Guard of an entered span, nothing without the `tracing` feature
+#[cfg(feature = "tracing")]
+pub(crate) type Guard = EnteredSpan;
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Guard;
+
+/// Span of the parsing of an email
+#[cfg(feature = "tracing")]
+pub(crate) fn parse_span(input: &str) -> Guard {
+    tracing::trace_span!("parse", input = %RedactedEmail(input)).entered()
+}
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn parse_span(_input: &str) -> Guard {
+    Guard
+}
+
+/// Input was rejected by the parser
+#[cfg(feature = "tracing")]
+pub(crate) fn invalid_email(reason: &'static str) {
+    tracing::trace!(reason, "invalid email");
+}
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn invalid_email(_reason: &'static str) {}
+
+/// Input was parsed into a normalized email
+#[cfg(feature = "tracing")]
+pub(crate) fn parsed(address: &str) {
+    tracing::trace!(email = %RedactedEmail(address), "parsed");
+}
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn parsed(_address: &str) {}
+
+/// A suffix of a domain was looked up in a list of the dataset
+#[cfg(feature = "tracing")]
+pub(crate) fn probe(list: &'static str, suffix: &str, matched: bool) {
+    tracing::trace!(list, suffix = %RedactedDomain(suffix), matched, "probe");
+}
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn probe(_list: &'static str, _suffix: &str, _matched: bool) {}
+
+/// Span of the validation of a parsed email
+#[cfg(feature = "tracing")]
+pub(crate) fn validate_span(email: &Email) -> Guard {
+    tracing::debug_span!("validate", email = %RedactedEmail(email.as_str())).entered()
+}
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn validate_span(_email: &Email) -> Guard {
+    Guard
+}
+
+/// Final decision on an email
+#[cfg(feature = "tracing")]
+pub(crate) fn decision(outcome: &Result<Validation>) {
+    match outcome {
+        Ok(validation) => tracing::debug!(
+            outcome = "ok",
+            tld = validation.tld.as_str(),
+            institution = validation
+                .institution_names
+                .as_ref()
+                .and_then(|names| names.first()),
+            "decision"
+        ),
+        Err(error) => tracing::debug!(outcome = error.code(), "decision"),
+    }
+}
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn decision(_outcome: &Result<Validation>) {}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::fmt;
+    use std::sync::Mutex;
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use super::*;
+
+    /// Subscriber keeping the fields of spans and events as lines
+    #[derive(Default)]
+    struct Capture(Mutex<Vec<String>>);
+
+    struct Line(String);
+
+    impl Visit for Line {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for &'static Capture {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut line = Line(span.metadata().name().to_string());
+            span.record(&mut line);
+            self.0.lock().unwrap().push(line.0);
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut line = Line(String::new());
+            event.record(&mut line);
+            self.0.lock().unwrap().push(line.0.trim_start().to_string());
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn capture(f: impl FnOnce()) -> Vec<String> {
+        let capture: &'static Capture = Box::leak(Box::default());
+        tracing::subscriber::with_default(capture, f);
+        capture.0.lock().unwrap().clone()
+    }
+
+    // Redaction is global, so it is only changed by this test
+    #[test]
+    fn test_spans_and_events() {
+        assert_eq!(redaction(), Redaction::LocalPart);
+        let lines = capture(|| {
+            let _ = crate::validate("Lee@CS.Stanford.edu");
+        });
+        // The FST backend stops probing once no key has the suffix, so only
+        // probes made by every backend are checked
+        for line in [
+            "parse input=<redacted>@CS.Stanford.edu",
+            "message=parsed email=<redacted>@cs.stanford.edu",
+            "validate email=<redacted>@cs.stanford.edu",
+            r#"message=probe list="stoplist" suffix=edu matched=false"#,
+            r#"message=probe list="stoplist" suffix=stanford.edu matched=false"#,
+            r#"message=probe list="institutions" suffix=stanford.edu matched=true"#,
+        ] {
+            assert!(lines.iter().any(|l| l == line), "{}\n{:#?}", line, lines);
+        }
+        // Institutions are not probed with the full domain
+        assert!(
+            !lines
+                .iter()
+                .any(|l| l.contains(r#""institutions" suffix=cs."#))
+        );
+        assert_eq!(
+            lines.last().unwrap(),
+            r#"message=decision outcome="ok" tld="edu" institution="Stanford University""#
+        );
+
+        let lines = capture(|| {
+            let _ = crate::Validator::default().validate("lee@gmail.com");
+        });
+        assert_eq!(
+            lines.last().unwrap(),
+            r#"message=decision outcome="free_mail_provider""#
+        );
+        let lines = capture(|| {
+            let _ = crate::validate("lee@@stanford.edu");
+        });
+        assert_eq!(
+            lines,
+            [
+                "parse input=<redacted>@stanford.edu",
+                r#"message=invalid email reason="invalid local part""#,
+            ]
+        );
+
+        // Hashes are only written with a key
+        set_redaction(Redaction::Hash);
+        let lines = capture(|| {
+            let _ = crate::validate("lee@stanford.edu");
+        });
+        assert_eq!(lines[0], "parse input=<redacted>");
+
+        let key = b"a secret of at least 32 bytes....";
+        set_redaction_key(key);
+        let hash = |value: &str| {
+            let mut mac = HmacSha256::new_from_slice(key).unwrap();
+            mac.update(value.as_bytes());
+            mac.finalize().into_bytes()[..HASH_LEN]
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        };
+        for (redaction, input, probe) in [
+            (
+                Redaction::None,
+                "parse input=lee@stanford.edu".to_string(),
+                "suffix=stanford.edu ".to_string(),
+            ),
+            (
+                Redaction::Hash,
+                format!("parse input={}", hash("lee@stanford.edu")),
+                format!("suffix={} ", hash("stanford.edu")),
+            ),
+            (
+                Redaction::All,
+                "parse input=<redacted>".to_string(),
+                "suffix=<redacted> ".to_string(),
+            ),
+        ] {
+            set_redaction(redaction);
+            let lines = capture(|| {
+                let _ = crate::validate("lee@stanford.edu");
+            });
+            assert_eq!(lines[0], input);
+            assert!(lines.iter().any(|l| l.contains(&probe)), "{:#?}", lines);
+        }
+        set_redaction(Redaction::default());
+    }
+}
@@ -1,19 +1,54 @@
-use self::data::{get_institution_names, is_abused, is_stop_list, is_under_tld};
-pub use self::email::Email;
-pub use self::error::{Error, Result};
+pub use self::affiliation::AffiliationHint;
+pub use self::batch::{BatchSummary, BatchValidation, ValidateIter};
+pub use self::cache::CacheStats;
+pub use self::cohort::CohortPolicy;
+use self::dataset::Dataset;
+pub use self::email::{Email, EmailRef};
+pub use self::error::{AbuseMode, Error, Result};
+#[cfg(feature = "tracing")]
+pub use self::instrument::{Redaction, redaction, set_redaction, set_redaction_key};
+pub use self::metrics::Metrics;
+pub use self::observer::{Decision, DecisionObserver, JsonLinesObserver, MatchSource};
+pub use self::reverification::{ReverificationPolicy, ReverificationReason};
+pub use self::role::RoleAccounts;
 pub use self::tld::Tld;
-pub use self::validation::Validation;
+pub use self::validation::{DomainValidation, Validation};
+pub use self::validator::Validator;
 
+mod affiliation;
+#[cfg(feature = "attestation")]
+pub mod attestation;
+mod batch;
+mod cache;
+mod cohort;
 mod data;
+pub mod dataset;
+pub mod dns;
 mod email;
 mod error;
+#[cfg(feature = "idn")]
+pub mod idn;
+mod impersonation;
+mod index;
+mod instrument;
+mod metrics;
+mod observer;
+#[cfg(feature = "ownership")]
+pub mod ownership;
+mod reverification;
+mod role;
+mod suggest;
 mod tld;
 mod validation;
+mod validator;
 
 /// Validate an email address.
 ///
 /// Will return an error if the email is not academic or has been banned.
 ///
+/// The lookup itself does not allocate: parsing an already normalized address
+/// allocates once, for the [`Email`] stored in the [`Validation`].
+///
 /// # Examples
 ///
 /// ```
@@ -25,32 +60,105 @@ where
     T: TryInto<Email>,
     T::Error: Into<Error>,
 {
-    let email = email.try_into().map_err(Into::into)?;
-    let domain_parts = email.domain_parts();
+    Dataset::embedded().validate(email)
+}
 
-    if is_stop_list(&domain_parts) {
-        return Err(Error::Stop);
-    }
+/// Validate a domain name.
+///
+/// Same as [`validate`] for any email of this domain. An invalid domain
+/// name is reported as [`Error::InvalidEmail`].
+///
+/// # Examples
+///
+/// ```
+/// let validation = rswot::validate_domain("stanford.edu");
+/// assert!(validation.is_ok());
+/// ```
+pub fn validate_domain(domain: &str) -> Result<DomainValidation> {
+    Dataset::embedded().validate_domain(domain)
+}
 
-    if is_abused(&domain_parts) {
-        return Err(Error::Abuse);
-    }
+/// Validate a batch of emails.
+///
+/// Results are in input order, with aggregate counts by error and top-level
+/// domain. Each distinct domain is only looked up once, and emails are
+/// validated in parallel with the `rayon` feature.
+///
+/// # Examples
+///
+/// ```
+/// let batch = rswot::validate_batch(["lreilly@stanford.edu", "lee@gmail.com"]);
+/// assert!(batch.results[0].is_ok());
+/// assert_eq!(batch.summary.errors[&rswot::Error::FreeMailProvider], 1);
+/// ```
+pub fn validate_batch<I>(emails: I) -> BatchValidation
+where
+    I: IntoIterator,
+    I::Item: TryInto<Email> + Send,
+    <I::Item as TryInto<Email>>::Error: Into<Error>,
+{
+    Dataset::embedded().validate_batch(emails)
+}
 
-    if is_under_tld(&domain_parts) {
-        return Ok(Validation::new(email));
-    }
+/// Validate emails lazily, in input order.
+///
+/// Same as [`validate_batch`] for inputs too large to collect, use
+/// [`BatchSummary::add`] to aggregate the results.
+///
+/// # Examples
+///
+/// ```
+/// let mut summary = rswot::BatchSummary::default();
+/// for result in rswot::validate_iter(["lreilly@stanford.edu", "lee@gmail.com"]) {
+///     summary.add(&result);
+/// }
+/// assert_eq!(summary.valid, 1);
+/// ```
+pub fn validate_iter<I>(emails: I) -> ValidateIter<I::IntoIter>
+where
+    I: IntoIterator,
+{
+    Dataset::embedded().validate_iter(emails)
+}
 
-    if let Some(institution_names) = get_institution_names(&domain_parts) {
-        return Ok(Validation::new(email).with_institutions(institution_names));
-    }
+/// Look up the names of the institution that a domain belongs to.
+///
+/// The domain or one of its parents must be registered as an institution,
+/// being under an academic suffix like `ac.za` is not enough.
+///
+/// # Examples
+///
+/// ```
+/// let names = rswot::institution_names("cs.Stanford.edu");
+/// assert!(names.is_some());
+/// ```
+pub fn institution_names(domain: &str) -> Option<&'static [&'static str]> {
+    let domain = email::parse_domain(domain).ok()?;
+    data::get_institution(&domain).map(|(_, names)| names)
+}
 
-    Err(Error::NotAcademic)
+/// Suggest corrections for an email whose domain is not academic.
+///
+/// See [`Dataset::suggest`].
+///
+/// # Examples
+///
+/// ```
+/// let suggestions = rswot::suggest("lee@ugr.ess");
+/// assert_eq!(suggestions[0].to_string(), "lee@ugr.es");
+/// ```
+pub fn suggest<T>(email: T) -> Vec<Email>
+where
+    T: TryInto<Email>,
+{
+    Dataset::embedded().suggest(email)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "names")]
     #[test]
     fn test_validate_basic() {
         let validation = validate("lreilly@stanford.edu").unwrap();
@@ -58,8 +166,58 @@ mod tests {
         assert_eq!(validation.tld.as_str(), "edu");
         assert_eq!(
             validation.institution_names.unwrap(),
-            vec!["Stanford University"]
+            ["Stanford University"]
+        );
+        assert_eq!(validation.affiliation, AffiliationHint::Unknown);
+    }
+
+    #[test]
+    fn test_validate_affiliation() {
+        let tests = [
+            ("lee@students.uonbi.ac.ke", AffiliationHint::Student),
+            ("lee@staff.uonbi.ac.ke", AffiliationHint::Staff),
+            ("lee@alumni.fh-aachen.de", AffiliationHint::Alumni),
+            ("lee@ugr.es", AffiliationHint::Unknown),
+        ];
+        for (email, affiliation) in tests {
+            assert_eq!(
+                validate(email).unwrap().affiliation,
+                affiliation,
+                "{}",
+                email
+            );
+        }
+    }
+
+    #[cfg(not(feature = "names"))]
+    #[test]
+    fn test_validate_without_names() {
+        let validation = validate("lreilly@stanford.edu").unwrap();
+        assert!(validation.institution_names.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate_email_ref() {
+        let email = EmailRef::parse("lreilly@stanford.edu").unwrap();
+        let validation = validate(email).unwrap();
+        assert_eq!(
+            validation.email,
+            Email::parse("LReilly@Stanford.edu").unwrap()
+        );
+        assert_eq!(validation.tld, Tld::EDU);
+    }
+
+    #[test]
+    fn test_validate_domain() {
+        let validation = validate_domain("Slac.Stanford.edu").unwrap();
+        assert_eq!(validation.tld, Tld::EDU);
+        assert!(validation.institution_names.is_some());
+        assert_eq!(
+            validate_domain("uct.ac.za").unwrap().institution_names,
+            None
         );
+        assert_eq!(validate_domain("gmail.com"), Err(Error::FreeMailProvider));
+        assert_eq!(validate_domain("gmail..com"), Err(Error::InvalidEmail));
     }
 
     #[test]
@@ -75,13 +233,23 @@ mod tests {
             ("lee@uottawa.ca", None),
             ("lee@ucy.ac.cy", None),
             ("lee@leerilly.net", Some(Error::NotAcademic)),
-            ("lee@gmail.com", Some(Error::Abuse)),
-            ("lee@stanford.edu.com", Some(Error::NotAcademic)),
-            ("lee@strath.ac.uk.com", Some(Error::NotAcademic)),
+            ("lee@gmail.com", Some(Error::FreeMailProvider)),
+            (
+                "lee@stanford.edu.com",
+                Some(Error::Deceptive {
+                    target: "stanford.edu".to_string(),
+                }),
+            ),
+            (
+                "lee@strath.ac.uk.com",
+                Some(Error::Deceptive {
+                    target: "strath.ac.uk".to_string(),
+                }),
+            ),
             ("john@stanford.edu", None),
             ("john@slac.stanford.edu", None),
             ("john@www.stanford.edu", None),
-            ("user@gmail.com", Some(Error::Abuse)),
+            ("user@gmail.com", Some(Error::FreeMailProvider)),
             ("", Some(Error::InvalidEmail)),
             ("the", Some(Error::InvalidEmail)),
             (" stanford.edu", Some(Error::InvalidEmail)),
@@ -91,11 +259,11 @@ mod tests {
             ("lee@stud.uni-corvinus.hu", None),
             ("lee@harvard.edu", None),
             ("lee@mail.harvard.edu", None),
-            ("imposter@si.edu", Some(Error::Abuse)),
+            ("imposter@si.edu", Some(Error::AbusedAcademicDomain)),
             ("lee@acmt.ac.ir", None),
             ("lee@australia.edu", Some(Error::Stop)),
-            ("john@si.edu", Some(Error::Abuse)),
-            ("john@foo.si.edu", Some(Error::Abuse)),
+            ("john@si.edu", Some(Error::AbusedAcademicDomain)),
+            ("john@foo.si.edu", Some(Error::AbusedAcademicDomain)),
             ("john@america.edu", Some(Error::Stop)),
             ("john@folger.edu", Some(Error::NotAcademic)),
             ("foo@bar.invalid", Some(Error::NotAcademic)),
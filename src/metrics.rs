@@ -0,0 +1,406 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::dataset;
+use crate::{Decision, DecisionObserver, Error, MatchSource, Result, Validation};
+
+/// Upper bounds of the latency buckets, in seconds
+const DURATION_BUCKETS: &[f64] = &[
+    0.000_001, 0.000_005, 0.000_01, 0.000_05, 0.000_1, 0.000_5, 0.001, 0.005, 0.01, 0.05, 0.1,
+];
+
+/// Label of the top-level domains that are not in the dataset, so arbitrary
+/// inputs can't create new series
+const OTHER_TLD: &str = "other";
+
+/// Label of the decisions that did not match any list
+const NO_MATCH_SOURCE: &str = "none";
+
+/// Counters and latency histograms of the decisions of a
+/// [`Validator`](crate::Validator), exported in the Prometheus text format
+///
+/// Metrics are a [`DecisionObserver`], shared with the validator through an
+/// [`Arc`](std::sync::Arc):
+///
+/// - `rswot_validations_total`, by `outcome`: `ok` or the
+///   [`code`](crate::Error::code) of the error
+/// - `rswot_validations_by_tld_total`, by `tld`: the top-level domain of the
+///   email, or `other` if the dataset has none of its institutions
+/// - `rswot_validations_by_match_source_total`, by `match_source`: the
+///   [`code`](crate::MatchSource::code) of the list that matched, or `none`
+/// - `rswot_validation_duration_seconds`, a histogram by `outcome`
+///
+/// The series are allocated up front and counted atomically, so observing a
+/// decision takes no lock and does not allocate. Every outcome and list is
+/// written from the start, even at 0, so that `rate()` and `increase()` see
+/// their first decision. Top-level domains are only written once counted, as
+/// there are hundreds of them.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use rswot::dataset::Dataset;
+/// use rswot::{Metrics, Validator};
+///
+/// let metrics = Arc::new(Metrics::new());
+/// let validator = Validator::new(Dataset::embedded()).with_observer(metrics.clone());
+/// let _ = validator.validate("lee@gmail.com");
+/// assert!(
+///     metrics
+///         .render()
+///         .contains("rswot_validations_total{outcome=\"free_mail_provider\"} 1\n")
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Metrics {
+    /// By index of [`OUTCOMES`]
+    outcomes: [AtomicU64; OUTCOMES.len()],
+    /// By index of [`dataset::top_level_domains`], then `other`
+    tlds: Box<[AtomicU64]>,
+    /// By index of [`MATCH_SOURCES`], then `none`
+    match_sources: [AtomicU64; MATCH_SOURCES.len() + 1],
+    /// By index of [`OUTCOMES`]
+    durations: [Histogram; OUTCOMES.len()],
+}
+
+/// Outcomes of validations, `ok` and the codes of the errors, see
+/// [`outcome_index`]
+const OUTCOMES: [&str; 15] = [
+    "ok",
+    "invalid_email",
+    "abuse",
+    "free_mail_provider",
+    "disposable_provider",
+    "abused_academic_domain",
+    "stop",
+    "not_academic",
+    "no_mail_server",
+    "suspected_impersonation",
+    "deceptive",
+    "mixed_script",
+    "confusable",
+    "role_account",
+    "cohort",
+];
+
+/// Lists of the dataset, in the order of their declaration
+const MATCH_SOURCES: [MatchSource; 6] = [
+    MatchSource::Institution,
+    MatchSource::AcademicSuffix,
+    MatchSource::Stoplist,
+    MatchSource::FreeMail,
+    MatchSource::Disposable,
+    MatchSource::Abused,
+];
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Count of each bucket, not cumulative, then of the durations over the
+    /// last one
+    buckets: [AtomicU64; DURATION_BUCKETS.len() + 1],
+    sum_nanos: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let i = DURATION_BUCKETS
+            .iter()
+            .position(|&le| seconds <= le)
+            .unwrap_or(DURATION_BUCKETS.len());
+        self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.sum_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics {
+            outcomes: Default::default(),
+            tlds: (0..=dataset::top_level_domains().len())
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            match_sources: Default::default(),
+            durations: Default::default(),
+        }
+    }
+
+    /// Metrics in the Prometheus text exposition format, version 0.0.4
+    ///
+    /// Counters are read one by one while validations go on, so they may be
+    /// off by the validations in progress.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        write_counter(
+            &mut out,
+            "rswot_validations_total",
+            "Validations by outcome",
+            "outcome",
+            OUTCOMES.into_iter().zip(&self.outcomes),
+        );
+        write_counter(
+            &mut out,
+            "rswot_validations_by_tld_total",
+            "Validations of emails by top-level domain",
+            "tld",
+            dataset::top_level_domains()
+                .iter()
+                .copied()
+                .chain([OTHER_TLD])
+                .zip(&*self.tlds)
+                .filter(|(_, count)| count.load(Ordering::Relaxed) > 0),
+        );
+        write_counter(
+            &mut out,
+            "rswot_validations_by_match_source_total",
+            "Validations of emails by list of the dataset that matched",
+            "match_source",
+            MATCH_SOURCES
+                .iter()
+                .map(MatchSource::code)
+                .chain([NO_MATCH_SOURCE])
+                .zip(&self.match_sources),
+        );
+
+        let name = "rswot_validation_duration_seconds";
+        writeln!(out, "# HELP {} Duration of validations by outcome", name).unwrap();
+        writeln!(out, "# TYPE {} histogram", name).unwrap();
+        for (outcome, histogram) in OUTCOMES.iter().zip(&self.durations) {
+            let buckets = histogram
+                .buckets
+                .each_ref()
+                .map(|count| count.load(Ordering::Relaxed));
+            // The count is the sum of the buckets so that they always agree
+            let count: u64 = buckets.iter().sum();
+            let mut cumulative = 0;
+            for (le, count) in DURATION_BUCKETS.iter().zip(buckets) {
+                cumulative += count;
+                writeln!(
+                    out,
+                    "{}_bucket{{outcome=\"{}\",le=\"{}\"}} {}",
+                    name, outcome, le, cumulative
+                )
+                .unwrap();
+            }
+            writeln!(
+                out,
+                "{}_bucket{{outcome=\"{}\",le=\"+Inf\"}} {}",
+                name, outcome, count
+            )
+            .unwrap();
+            let sum = Duration::from_nanos(histogram.sum_nanos.load(Ordering::Relaxed));
+            writeln!(
+                out,
+                "{}_sum{{outcome=\"{}\"}} {}",
+                name,
+                outcome,
+                sum.as_secs_f64()
+            )
+            .unwrap();
+            writeln!(out, "{}_count{{outcome=\"{}\"}} {}", name, outcome, count).unwrap();
+        }
+        out
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Metrics {
+        Metrics::new()
+    }
+}
+
+impl DecisionObserver for Metrics {
+    fn observe(&self, decision: &Decision<'_>) {
+        let outcome = outcome_index(decision.outcome);
+        self.outcomes[outcome].fetch_add(1, Ordering::Relaxed);
+        if let Some(email) = decision.email {
+            self.tlds[tld_index(email.domain())].fetch_add(1, Ordering::Relaxed);
+            let match_source = decision
+                .match_source
+                .map_or(MATCH_SOURCES.len(), |source| source as usize);
+            self.match_sources[match_source].fetch_add(1, Ordering::Relaxed);
+        }
+        self.durations[outcome].observe(decision.duration);
+    }
+}
+
+/// Index of an outcome in [`OUTCOMES`]
+fn outcome_index(outcome: &Result<Validation>) -> usize {
+    let Err(error) = outcome else {
+        return 0;
+    };
+    match error {
+        Error::InvalidEmail => 1,
+        Error::Abuse => 2,
+        Error::FreeMailProvider => 3,
+        Error::DisposableProvider => 4,
+        Error::AbusedAcademicDomain => 5,
+        Error::Stop => 6,
+        Error::NotAcademic => 7,
+        Error::NoMailServer => 8,
+        Error::SuspectedImpersonation { .. } => 9,
+        Error::Deceptive { .. } => 10,
+        Error::MixedScript => 11,
+        Error::Confusable { .. } => 12,
+        Error::RoleAccount => 13,
+        Error::Cohort { .. } => 14,
+    }
+}
+
+/// Index of the top-level domain of a normalized domain in
+/// [`dataset::top_level_domains`], or after the last one if it is not in the
+/// dataset
+fn tld_index(domain: &str) -> usize {
+    let tld = domain.rsplit('.').next().unwrap_or(domain);
+    let tlds = dataset::top_level_domains();
+    tlds.binary_search(&tld).unwrap_or(tlds.len())
+}
+
+/// Write a counter with a single label, `label` values must not need escaping
+///
+fn write_counter<'a>(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    values: impl Iterator<Item = (&'static str, &'a AtomicU64)>,
+) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} counter", name).unwrap();
+    for (value, count) in values {
+        let count = count.load(Ordering::Relaxed);
+        writeln!(out, "{}{{{}=\"{}\"}} {}", name, label, value, count).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Email;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new();
+        // Outcomes and lists are written before any decision
+        let rendered = metrics.render();
+        for outcome in OUTCOMES {
+            for line in [
+                format!("rswot_validations_total{{outcome=\"{}\"}} 0\n", outcome),
+                format!(
+                    "rswot_validation_duration_seconds_bucket{{outcome=\"{}\",le=\"+Inf\"}} 0\n",
+                    outcome
+                ),
+                format!(
+                    "rswot_validation_duration_seconds_sum{{outcome=\"{}\"}} 0\n",
+                    outcome
+                ),
+                format!(
+                    "rswot_validation_duration_seconds_count{{outcome=\"{}\"}} 0\n",
+                    outcome
+                ),
+            ] {
+                assert!(rendered.contains(&line), "{}\n{}", line, rendered);
+            }
+        }
+        for source in ["institution", "free_mail", "abused", "none"] {
+            let line = format!(
+                "rswot_validations_by_match_source_total{{match_source=\"{}\"}} 0\n",
+                source
+            );
+            assert!(rendered.contains(&line), "{}\n{}", line, rendered);
+        }
+        assert!(!rendered.contains("rswot_validations_by_tld_total{"));
+
+        let email = Email::parse("lee@example.invalid").unwrap();
+        let decision = Decision {
+            input: Some("lee@example.invalid"),
+            email: Some(&email),
+            outcome: &Err(Error::FreeMailProvider),
+            match_source: Some(MatchSource::FreeMail),
+            dataset_version: dataset::VERSION,
+            dataset_hash: None,
+            duration: Duration::from_micros(20),
+        };
+        metrics.observe(&decision);
+        metrics.observe(&decision);
+        metrics.observe(&Decision {
+            input: None,
+            email: None,
+            outcome: &Err(Error::InvalidEmail),
+            match_source: None,
+            duration: Duration::from_secs(1),
+            ..decision
+        });
+
+        let rendered = metrics.render();
+        for line in [
+            "# TYPE rswot_validations_total counter\n",
+            "rswot_validations_total{outcome=\"free_mail_provider\"} 2\n",
+            "rswot_validations_total{outcome=\"invalid_email\"} 1\n",
+            "rswot_validations_by_tld_total{tld=\"other\"} 2\n",
+            "rswot_validations_by_match_source_total{match_source=\"free_mail\"} 2\n",
+            "# TYPE rswot_validation_duration_seconds histogram\n",
+            "rswot_validation_duration_seconds_bucket{outcome=\"free_mail_provider\",le=\"0.00001\"} 0\n",
+            "rswot_validation_duration_seconds_bucket{outcome=\"free_mail_provider\",le=\"0.00005\"} 2\n",
+            "rswot_validation_duration_seconds_bucket{outcome=\"free_mail_provider\",le=\"+Inf\"} 2\n",
+            "rswot_validation_duration_seconds_count{outcome=\"free_mail_provider\"} 2\n",
+            "rswot_validation_duration_seconds_bucket{outcome=\"invalid_email\",le=\"0.1\"} 0\n",
+            "rswot_validation_duration_seconds_bucket{outcome=\"invalid_email\",le=\"+Inf\"} 1\n",
+            "rswot_validation_duration_seconds_sum{outcome=\"invalid_email\"} 1\n",
+            "rswot_validations_total{outcome=\"ok\"} 0\n",
+            "rswot_validations_by_match_source_total{match_source=\"none\"} 0\n",
+            "rswot_validation_duration_seconds_count{outcome=\"ok\"} 0\n",
+        ] {
+            assert!(rendered.contains(line), "{}\n{}", line, rendered);
+        }
+        assert_eq!(
+            rendered.matches("rswot_validations_by_tld_total{").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_tld_index() {
+        // The dataset may be restricted to some regions
+        let tlds = dataset::top_level_domains();
+        assert_eq!(tld_index(&format!("cs.example.{}", tlds[1])), 1);
+        assert_eq!(tld_index("example.invalid"), tlds.len());
+    }
+
+    #[test]
+    fn test_outcome_index() {
+        let errors = [
+            Error::InvalidEmail,
+            Error::Abuse,
+            Error::FreeMailProvider,
+            Error::DisposableProvider,
+            Error::AbusedAcademicDomain,
+            Error::Stop,
+            Error::NotAcademic,
+            Error::NoMailServer,
+            Error::SuspectedImpersonation {
+                target: "stanford.edu".to_string(),
+            },
+            Error::Deceptive {
+                target: "stanford.edu".to_string(),
+            },
+            Error::MixedScript,
+            Error::Confusable {
+                target: "stanford.edu".to_string(),
+            },
+            Error::RoleAccount,
+            Error::Cohort { year: 2019 },
+        ];
+        for error in errors {
+            assert_eq!(OUTCOMES[outcome_index(&Err(error.clone()))], error.code());
+        }
+        for (i, source) in MATCH_SOURCES.into_iter().enumerate() {
+            assert_eq!(source as usize, i);
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::dataset::DatasetHash;
+use crate::{DomainValidation, Email, Error, Result, Validation};
+
+/// List of the dataset that decided the outcome of a validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum MatchSource {
+    /// Domain of an institution
+    Institution,
+    /// Academic suffix, like `ac.uk`
+    AcademicSuffix,
+    Stoplist,
+    FreeMail,
+    Disposable,
+    /// Academic domains known for abuse
+    Abused,
+}
+
+impl MatchSource {
+    /// Stable identifier of the list, like `free_mail`
+    pub fn code(&self) -> &'static str {
+        match self {
+            MatchSource::Institution => "institution",
+            MatchSource::AcademicSuffix => "academic_suffix",
+            MatchSource::Stoplist => "stoplist",
+            MatchSource::FreeMail => "free_mail",
+            MatchSource::Disposable => "disposable",
+            MatchSource::Abused => "abused",
+        }
+    }
+
+    /// List that matched a domain looked up in the dataset, `None` if the
+    /// domain is in none of them
+    pub(crate) fn of(decision: &Result<DomainValidation>) -> Option<MatchSource> {
+        match decision {
+            Ok(validation) if validation.institution_names.is_some() => {
+                Some(MatchSource::Institution)
+            }
+            Ok(_) => Some(MatchSource::AcademicSuffix),
+            Err(Error::Stop) => Some(MatchSource::Stoplist),
+            Err(Error::FreeMailProvider) => Some(MatchSource::FreeMail),
+            Err(Error::DisposableProvider) => Some(MatchSource::Disposable),
+            Err(Error::AbusedAcademicDomain) => Some(MatchSource::Abused),
+            Err(_) => None,
+        }
+    }
+}
+
+thread_local! {
+    /// Input of the first email parsed on this thread since [`capture_input`]
+    /// started, `None` outside of it
+    static INPUT: RefCell<Option<Option<String>>> = const { RefCell::new(None) };
+}
+
+/// Call `f` and return the input of the first email it parsed, if any, so
+/// observers get inputs that conversions to [`Email`] don't keep
+pub(crate) fn capture_input<T>(f: impl FnOnce() -> T) -> (T, Option<String>) {
+    let outer = INPUT.replace(Some(None));
+    let value = f();
+    let input = INPUT.replace(outer).flatten();
+    (value, input)
+}
+
+/// Record the input of an email being parsed, only within [`capture_input`]
+pub(crate) fn record_input(input: &str) {
+    INPUT.with_borrow_mut(|captured| {
+        if let Some(captured @ None) = captured {
+            *captured = Some(input.to_string());
+        }
+    });
+}
+
+/// Decision of a [`Validator`](crate::Validator) on an email
+#[derive(Debug, Clone, Copy)]
+pub struct Decision<'a> {
+    /// Input as given to the validator, even if it is not an email
+    ///
+    /// Inputs that are not parsed from a string, like an [`Email`], are
+    /// formatted once converted. `None` only if such a conversion failed.
+    pub input: Option<&'a str>,
+    /// Normalized email, `None` if the input is not an email
+    pub email: Option<&'a Email>,
+    /// Outcome returned by the validator
+    pub outcome: &'a Result<Validation>,
+    /// List that matched the domain, even if the outcome is rejected by a later
+    /// check like [`Validator::with_cohort_policy`](crate::Validator::with_cohort_policy)
+    ///
+    /// `None` if the domain was not looked up or is in none of the lists.
+    pub match_source: Option<MatchSource>,
+    /// Version of the crate and of its embedded dataset, see
+    /// [`dataset::VERSION`](crate::dataset::VERSION)
+    pub dataset_version: &'static str,
+    /// Hash of the dataset the domain was looked up in, `None` if it was not
+    pub dataset_hash: Option<DatasetHash>,
+    /// Time taken to parse and validate the email
+    pub duration: Duration,
+}
+
+/// Hook invoked with every decision of a [`Validator`](crate::Validator), like
+/// for an audit log, see [`Validator::with_observer`](crate::Validator::with_observer)
+///
+/// Observers are invoked on the thread that validated the email, after the
+/// decision and before it is returned, so they should be quick.
+pub trait DecisionObserver: Send + Sync {
+    fn observe(&self, decision: &Decision<'_>);
+}
+
+impl<T: DecisionObserver + ?Sized> DecisionObserver for Arc<T> {
+    fn observe(&self, decision: &Decision<'_>) {
+        (**self).observe(decision)
+    }
+}
+
+/// Observer writing every decision as a line of JSON
+///
+/// Lines have the time of the decision in seconds since the Unix epoch, the
+/// input, the normalized email, the outcome (`ok` or the
+/// [`code`](Error::code) of the error), the matched list and the dataset:
+///
+/// ```json
+/// {"time":1790000000,"input":"Lee@Stanford.edu","email":"lee@stanford.edu","outcome":"ok","match_source":"institution","dataset_version":"0.1.88","dataset_hash":"93080609badc8a7a"}
+/// ```
+///
+/// Writing can't fail the validation, the first error is kept instead, see
+/// [`take_error`](JsonLinesObserver::take_error).
+///
+/// # Examples
+///
+/// ```no_run
+/// use rswot::dataset::Dataset;
+/// use rswot::{JsonLinesObserver, Validator};
+///
+/// let observer = JsonLinesObserver::append("decisions.jsonl").unwrap();
+/// let validator = Validator::new(Dataset::embedded()).with_observer(observer);
+/// assert!(validator.validate("lreilly@stanford.edu").is_ok());
+/// ```
+#[derive(Debug)]
+pub struct JsonLinesObserver<W> {
+    writer: Mutex<W>,
+    error: Mutex<Option<io::Error>>,
+}
+
+impl JsonLinesObserver<LineWriter<File>> {
+    /// Append decisions to a file, created if missing, flushed after each line
+    pub fn append(path: impl AsRef<Path>) -> io::Result<JsonLinesObserver<LineWriter<File>>> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(JsonLinesObserver::new(LineWriter::new(file)))
+    }
+}
+
+impl<W: Write + Send> JsonLinesObserver<W> {
+    pub fn new(writer: W) -> JsonLinesObserver<W> {
+        JsonLinesObserver {
+            writer: Mutex::new(writer),
+            error: Mutex::new(None),
+        }
+    }
+
+    /// First error writing a decision since the last call, if any
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.error.lock().unwrap().take()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap()
+    }
+
+    fn write(&self, decision: &Decision<'_>, time: SystemTime) -> io::Result<()> {
+        let mut line = String::from("{");
+        let seconds = time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        line.push_str(&format!("\"time\":{}", seconds));
+        push_field(&mut line, "input", decision.input);
+        push_field(&mut line, "email", decision.email.map(Email::as_str));
+        let outcome = match decision.outcome {
+            Ok(_) => "ok",
+            Err(error) => error.code(),
+        };
+        push_field(&mut line, "outcome", Some(outcome));
+        push_field(
+            &mut line,
+            "match_source",
+            decision.match_source.as_ref().map(MatchSource::code),
+        );
+        push_field(&mut line, "dataset_version", Some(decision.dataset_version));
+        let dataset_hash = decision.dataset_hash.map(|hash| hash.to_string());
+        push_field(&mut line, "dataset_hash", dataset_hash.as_deref());
+        line.push_str("}\n");
+
+        self.writer.lock().unwrap().write_all(line.as_bytes())
+    }
+}
+
+impl<W: Write + Send> DecisionObserver for JsonLinesObserver<W> {
+    fn observe(&self, decision: &Decision<'_>) {
+        if let Err(error) = self.write(decision, SystemTime::now()) {
+            self.error.lock().unwrap().get_or_insert(error);
+        }
+    }
+}
+
+/// Append `,"key":value` to a JSON object, `null` if the value is `None`
+fn push_field(line: &mut String, key: &str, value: Option<&str>) {
+    line.push_str(&format!(",\"{}\":", key));
+    let Some(value) = value else {
+        line.push_str("null");
+        return;
+    };
+    line.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c if c.is_control() => line.push_str(&format!("\\u{:04x}", c as u32)),
+            c => line.push(c),
+        }
+    }
+    line.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_source() {
+        let dataset = crate::dataset::Dataset::embedded();
+        let tests = [
+            ("stanford.edu", Some(MatchSource::Institution)),
+            ("cs.strath.ac.uk", Some(MatchSource::Institution)),
+            ("example.ac.za", Some(MatchSource::AcademicSuffix)),
+            ("2011.cqut.edu.cn", Some(MatchSource::Stoplist)),
+            ("gmail.com", Some(MatchSource::FreeMail)),
+            ("mailinator.com", Some(MatchSource::Disposable)),
+            ("example.com", None),
+        ];
+        for (domain, source) in tests {
+            assert_eq!(
+                MatchSource::of(&dataset.lookup(domain)),
+                source,
+                "{}",
+                domain
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let observer = JsonLinesObserver::new(Vec::new());
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_790_000_000);
+        let email = Email::parse("Lee@gmail.com").unwrap();
+        let decision = Decision {
+            input: Some("\"Lee\"@gmail.com\n"),
+            email: Some(&email),
+            outcome: &Err(Error::FreeMailProvider),
+            match_source: Some(MatchSource::FreeMail),
+            dataset_version: "0.1.0",
+            dataset_hash: Some("93080609badc8a7a".parse().unwrap()),
+            duration: Duration::from_micros(20),
+        };
+        observer.write(&decision, time).unwrap();
+        let decision = Decision {
+            input: None,
+            email: None,
+            outcome: &Err(Error::InvalidEmail),
+            match_source: None,
+            dataset_hash: None,
+            ..decision
+        };
+        observer.write(&decision, time).unwrap();
+
+        assert_eq!(
+            String::from_utf8(observer.into_inner()).unwrap(),
+            concat!(
+                r#"{"time":1790000000,"input":"\"Lee\"@gmail.com\n","email":"lee@gmail.com","outcome":"free_mail_provider","match_source":"free_mail","dataset_version":"0.1.0","dataset_hash":"93080609badc8a7a"}"#,
+                "\n",
+                r#"{"time":1790000000,"input":null,"email":null,"outcome":"invalid_email","match_source":null,"dataset_version":"0.1.0","dataset_hash":null}"#,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_json_lines_input() {
+        let observer = Arc::new(JsonLinesObserver::new(Vec::new()));
+        let validator = crate::Validator::default().with_observer(observer.clone());
+        assert_eq!(validator.validate(" Lee@@x "), Err(Error::InvalidEmail));
+        drop(validator);
+
+        let observer = Arc::into_inner(observer).unwrap();
+        let lines = String::from_utf8(observer.into_inner()).unwrap();
+        assert!(
+            lines.contains(r#","input":" Lee@@x ","email":null,"outcome":"invalid_email","#),
+            "{}",
+            lines
+        );
+    }
+}
@@ -0,0 +1,591 @@
+//! Verification that users own the mailbox of their email.
+//!
+//! Validation only tells that a domain is academic. Once it succeeds, a
+//! [`Workflow`] issues a one-time secret bound to the email, a numeric code or a
+//! token for a magic link, keeps it in a [`CodeStore`] and delivers it through a
+//! [`Mailer`]. The user proves ownership by handing the secret back before it
+//! expires and within a limited number of attempts, which resending the secret
+//! does not reset.
+//!
+//! Stores and mailers are traits, with in-memory implementations for tests.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::SystemTime;
+//!
+//! use rswot::ownership::{InMemoryCodeStore, InMemoryMailer, Workflow};
+//!
+//! let workflow = Workflow::new(InMemoryCodeStore::new(), InMemoryMailer::new());
+//! let validation = rswot::validate("lreilly@stanford.edu").unwrap();
+//! let now = SystemTime::now();
+//! workflow.start(&validation, now).unwrap();
+//!
+//! let code = workflow.mailer().last_secret(&validation.email).unwrap();
+//! assert!(workflow.verify(&validation.email, &code, now).is_ok());
+//! // Secrets are one-time
+//! assert!(workflow.verify(&validation.email, &code, now).is_err());
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::ops::RangeInclusive;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use thiserror::Error;
+
+use crate::{Email, Validation};
+
+/// Reason ownership was not verified
+#[derive(Error, Debug)]
+pub enum OwnershipError {
+    #[error("No verification is pending for this email")]
+    NotStarted,
+
+    #[error("Verification secret is expired")]
+    Expired,
+
+    #[error("Too many attempts to verify this email")]
+    TooManyAttempts,
+
+    #[error("Verification secret is invalid")]
+    InvalidSecret,
+
+    /// The workflow can't issue secrets, like codes with too many digits
+    #[error("Invalid verification workflow: {0}")]
+    InvalidWorkflow(&'static str),
+
+    /// The store, the mailer or the random number generator failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Number of digits of codes, shorter ones are too easy to guess and longer
+/// ones don't fit in a `u64`
+pub const CODE_DIGITS: RangeInclusive<u32> = 4..=19;
+
+/// Kind of secret sent to users
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecretKind {
+    /// Numeric code of this many digits, typed by the user, in [`CODE_DIGITS`]
+    Code { digits: u32 },
+    /// 32 random bytes in hexadecimal, for a magic link
+    LinkToken,
+}
+
+impl SecretKind {
+    fn generate(self) -> Result<String, OwnershipError> {
+        match self {
+            SecretKind::Code { digits } => {
+                if !CODE_DIGITS.contains(&digits) {
+                    return Err(OwnershipError::InvalidWorkflow(
+                        "codes must have between 4 and 19 digits",
+                    ));
+                }
+                let modulus = 10_u64.pow(digits);
+                // Rejection sampling, so every code is as likely
+                let limit = u64::MAX - u64::MAX % modulus;
+                loop {
+                    let value = getrandom::u64().map_err(io::Error::other)?;
+                    if value < limit {
+                        return Ok(format!(
+                            "{:0width$}",
+                            value % modulus,
+                            width = digits as usize
+                        ));
+                    }
+                }
+            }
+            SecretKind::LinkToken => {
+                let mut bytes = [0; 32];
+                getrandom::fill(&mut bytes).map_err(io::Error::other)?;
+                Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+            }
+        }
+    }
+}
+
+/// Pending verification of an email
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Challenge {
+    /// Secret sent to the user
+    pub secret: String,
+    /// When the secret expires
+    pub expires_at: SystemTime,
+    /// Attempts so far, counted before the secret is checked, including the
+    /// ones on the secrets it replaced
+    pub attempts: u32,
+}
+
+/// Message delivered by a [`Mailer`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Message {
+    /// Kind of the secret, to word the message or build a link
+    pub kind: SecretKind,
+    /// Secret to hand back
+    pub secret: String,
+    /// When the secret expires
+    pub expires_at: SystemTime,
+}
+
+/// Storage of the pending verifications, one per email
+///
+/// Secrets are stored as is, so the store must only be readable by the server.
+pub trait CodeStore {
+    /// Store the challenge of an email, replacing the previous one but keeping
+    /// its attempts if it has not expired at `now`, and return it as stored
+    ///
+    /// This must be atomic with [`increment_attempts`](CodeStore::increment_attempts),
+    /// so reissuing a secret never loses attempts.
+    fn issue(&self, email: &Email, challenge: Challenge, now: SystemTime) -> io::Result<Challenge>;
+
+    /// Challenge of an email, if any
+    fn get(&self, email: &Email) -> io::Result<Option<Challenge>>;
+
+    /// Remove the challenge of an email, if any
+    fn remove(&self, email: &Email) -> io::Result<()>;
+
+    /// Count an attempt on the challenge of an email, and return it with the
+    /// attempt counted, if any
+    ///
+    /// This must be atomic: concurrent attempts each get a different count, so
+    /// parallel guesses can't get past the limit.
+    fn increment_attempts(&self, email: &Email) -> io::Result<Option<Challenge>>;
+}
+
+/// Delivery of secrets to the mailbox of an email
+pub trait Mailer {
+    fn send(&self, email: &Email, message: &Message) -> io::Result<()>;
+}
+
+/// Store keeping challenges in memory
+#[derive(Debug, Default)]
+pub struct InMemoryCodeStore {
+    challenges: Mutex<HashMap<Email, Challenge>>,
+}
+
+impl InMemoryCodeStore {
+    pub fn new() -> InMemoryCodeStore {
+        InMemoryCodeStore::default()
+    }
+}
+
+impl CodeStore for InMemoryCodeStore {
+    fn issue(
+        &self,
+        email: &Email,
+        mut challenge: Challenge,
+        now: SystemTime,
+    ) -> io::Result<Challenge> {
+        let mut challenges = self.challenges.lock().unwrap();
+        if let Some(previous) = challenges.get(email) {
+            if now < previous.expires_at {
+                challenge.attempts = previous.attempts;
+            }
+        }
+        challenges.insert(email.clone(), challenge.clone());
+        Ok(challenge)
+    }
+
+    fn get(&self, email: &Email) -> io::Result<Option<Challenge>> {
+        Ok(self.challenges.lock().unwrap().get(email).cloned())
+    }
+
+    fn remove(&self, email: &Email) -> io::Result<()> {
+        self.challenges.lock().unwrap().remove(email);
+        Ok(())
+    }
+
+    fn increment_attempts(&self, email: &Email) -> io::Result<Option<Challenge>> {
+        let mut challenges = self.challenges.lock().unwrap();
+        Ok(challenges.get_mut(email).map(|challenge| {
+            challenge.attempts += 1;
+            challenge.clone()
+        }))
+    }
+}
+
+/// Mailer keeping the messages in memory instead of sending them
+#[derive(Debug, Default)]
+pub struct InMemoryMailer {
+    sent: Mutex<Vec<(Email, Message)>>,
+}
+
+impl InMemoryMailer {
+    pub fn new() -> InMemoryMailer {
+        InMemoryMailer::default()
+    }
+
+    /// Messages sent so far, the oldest first
+    pub fn sent(&self) -> Vec<(Email, Message)> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Secret of the last message sent to an email
+    pub fn last_secret(&self, email: &Email) -> Option<String> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(to, _)| to == email)
+            .map(|(_, message)| message.secret.clone())
+    }
+}
+
+impl Mailer for InMemoryMailer {
+    fn send(&self, email: &Email, message: &Message) -> io::Result<()> {
+        self.sent
+            .lock()
+            .unwrap()
+            .push((email.clone(), message.clone()));
+        Ok(())
+    }
+}
+
+impl<T: CodeStore + ?Sized> CodeStore for &T {
+    fn issue(&self, email: &Email, challenge: Challenge, now: SystemTime) -> io::Result<Challenge> {
+        (**self).issue(email, challenge, now)
+    }
+
+    fn get(&self, email: &Email) -> io::Result<Option<Challenge>> {
+        (**self).get(email)
+    }
+
+    fn remove(&self, email: &Email) -> io::Result<()> {
+        (**self).remove(email)
+    }
+
+    fn increment_attempts(&self, email: &Email) -> io::Result<Option<Challenge>> {
+        (**self).increment_attempts(email)
+    }
+}
+
+impl<T: Mailer + ?Sized> Mailer for &T {
+    fn send(&self, email: &Email, message: &Message) -> io::Result<()> {
+        (**self).send(email, message)
+    }
+}
+
+/// Issues and checks the secrets proving ownership of emails
+///
+/// By default, secrets are 6 digit codes valid for 15 minutes and 5 attempts.
+#[derive(Debug)]
+pub struct Workflow<S, M> {
+    store: S,
+    mailer: M,
+    kind: SecretKind,
+    ttl: Duration,
+    max_attempts: u32,
+}
+
+impl<S: CodeStore, M: Mailer> Workflow<S, M> {
+    pub fn new(store: S, mailer: M) -> Workflow<S, M> {
+        Workflow {
+            store,
+            mailer,
+            kind: SecretKind::Code { digits: 6 },
+            ttl: Duration::from_secs(15 * 60),
+            max_attempts: 5,
+        }
+    }
+
+    /// Send secrets of this kind, codes must have a number of digits in
+    /// [`CODE_DIGITS`]
+    pub fn with_secret_kind(self, kind: SecretKind) -> Workflow<S, M> {
+        Workflow { kind, ..self }
+    }
+
+    /// Expire secrets after `ttl`
+    pub fn with_ttl(self, ttl: Duration) -> Workflow<S, M> {
+        Workflow { ttl, ..self }
+    }
+
+    /// Refuse secrets after `max_attempts` failed attempts, until they expire
+    pub fn with_max_attempts(self, max_attempts: u32) -> Workflow<S, M> {
+        Workflow {
+            max_attempts,
+            ..self
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn mailer(&self) -> &M {
+        &self.mailer
+    }
+
+    /// Issue a secret for a validated email and send it, replacing any pending
+    /// one.
+    ///
+    /// The attempts on a pending secret are kept, so resending a secret does
+    /// not allow more guesses, and no secret is sent once they are all used
+    /// until the pending secret expires.
+    pub fn start(&self, validation: &Validation, now: SystemTime) -> Result<(), OwnershipError> {
+        let email = &validation.email;
+        let expires_at = now
+            .checked_add(self.ttl)
+            .ok_or(OwnershipError::InvalidWorkflow("ttl is too long"))?;
+        if let Some(pending) = self.store.get(email)? {
+            if now < pending.expires_at && pending.attempts >= self.max_attempts {
+                return Err(OwnershipError::TooManyAttempts);
+            }
+        }
+        let secret = self.kind.generate()?;
+        let challenge = Challenge {
+            secret: secret.clone(),
+            expires_at,
+            attempts: 0,
+        };
+        // Attempts may have been made since the pending secret was read
+        if self.store.issue(email, challenge, now)?.attempts >= self.max_attempts {
+            return Err(OwnershipError::TooManyAttempts);
+        }
+        let message = Message {
+            kind: self.kind,
+            secret,
+            expires_at,
+        };
+        self.mailer.send(email, &message)?;
+        Ok(())
+    }
+
+    /// Check the secret handed back for an email at `now`.
+    ///
+    /// A secret is only accepted once. Attempts are counted before the secret
+    /// is checked, and the secret is dropped once it expired. After too many
+    /// attempts, it is kept until it expires so that it can't be reissued.
+    pub fn verify(
+        &self,
+        email: &Email,
+        secret: &str,
+        now: SystemTime,
+    ) -> Result<(), OwnershipError> {
+        let Some(challenge) = self.store.increment_attempts(email)? else {
+            return Err(OwnershipError::NotStarted);
+        };
+        if now >= challenge.expires_at {
+            self.store.remove(email)?;
+            return Err(OwnershipError::Expired);
+        }
+        if challenge.attempts > self.max_attempts {
+            return Err(OwnershipError::TooManyAttempts);
+        }
+
+        if constant_time_eq(challenge.secret.as_bytes(), secret.trim().as_bytes()) {
+            self.store.remove(email)?;
+            return Ok(());
+        }
+        Err(OwnershipError::InvalidSecret)
+    }
+}
+
+/// Compare secrets in a time that does not depend on where they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a
+        .iter()
+        .zip(b)
+        .fold(0, |difference, (x, y)| difference | (x ^ y));
+    std::hint::black_box(difference) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_790_000_000)
+    }
+
+    fn workflow() -> Workflow<InMemoryCodeStore, InMemoryMailer> {
+        Workflow::new(InMemoryCodeStore::new(), InMemoryMailer::new())
+    }
+
+    #[test]
+    fn test_verify() {
+        let workflow = workflow();
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        let email = &validation.email;
+        assert!(matches!(
+            workflow.verify(email, "123456", now()),
+            Err(OwnershipError::NotStarted)
+        ));
+
+        workflow.start(&validation, now()).unwrap();
+        let sent = workflow.mailer().sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(&sent[0].0, email);
+        let code = &sent[0].1.secret;
+        assert_eq!(code.len(), 6);
+        assert!(code.bytes().all(|b| b.is_ascii_digit()));
+
+        let wrong = if code == "000000" { "000001" } else { "000000" };
+        assert!(matches!(
+            workflow.verify(email, wrong, now()),
+            Err(OwnershipError::InvalidSecret)
+        ));
+        assert_eq!(workflow.store().get(email).unwrap().unwrap().attempts, 1);
+        assert!(
+            workflow
+                .verify(email, &format!(" {} ", code), now())
+                .is_ok()
+        );
+        assert!(matches!(
+            workflow.verify(email, code, now()),
+            Err(OwnershipError::NotStarted)
+        ));
+    }
+
+    #[test]
+    fn test_expiry_and_attempts() {
+        let workflow = workflow().with_max_attempts(2);
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        let email = &validation.email;
+
+        workflow.start(&validation, now()).unwrap();
+        let code = workflow.mailer().last_secret(email).unwrap();
+        let later = now() + Duration::from_secs(15 * 60);
+        assert!(matches!(
+            workflow.verify(email, &code, later),
+            Err(OwnershipError::Expired)
+        ));
+
+        workflow.start(&validation, now()).unwrap();
+        let code = workflow.mailer().last_secret(email).unwrap();
+        for _ in 0..2 {
+            assert!(matches!(
+                workflow.verify(email, "not a code", now()),
+                Err(OwnershipError::InvalidSecret)
+            ));
+        }
+        assert!(matches!(
+            workflow.verify(email, &code, now()),
+            Err(OwnershipError::TooManyAttempts)
+        ));
+    }
+
+    #[test]
+    fn test_reissue() {
+        let workflow = workflow();
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        let email = &validation.email;
+
+        // Resending keeps the attempts
+        workflow.start(&validation, now()).unwrap();
+        assert!(workflow.verify(email, "not a code", now()).is_err());
+        workflow.start(&validation, now()).unwrap();
+        assert_eq!(workflow.store().get(email).unwrap().unwrap().attempts, 1);
+        for _ in 0..4 {
+            assert!(matches!(
+                workflow.verify(email, "not a code", now()),
+                Err(OwnershipError::InvalidSecret)
+            ));
+        }
+
+        // Once they are all used, no secret is sent until the pending one expires
+        assert!(matches!(
+            workflow.start(&validation, now()),
+            Err(OwnershipError::TooManyAttempts)
+        ));
+        assert_eq!(workflow.mailer().sent().len(), 2);
+        assert!(matches!(
+            workflow.verify(email, "not a code", now()),
+            Err(OwnershipError::TooManyAttempts)
+        ));
+        let code = workflow.mailer().last_secret(email).unwrap();
+        assert!(matches!(
+            workflow.verify(email, &code, now()),
+            Err(OwnershipError::TooManyAttempts)
+        ));
+
+        let later = now() + Duration::from_secs(15 * 60);
+        workflow.start(&validation, later).unwrap();
+        assert_eq!(workflow.store().get(email).unwrap().unwrap().attempts, 0);
+        let code = workflow.mailer().last_secret(email).unwrap();
+        assert!(workflow.verify(email, &code, later).is_ok());
+    }
+
+    #[test]
+    fn test_parallel_attempts() {
+        use std::sync::Barrier;
+        use std::thread;
+
+        let workflow = workflow().with_max_attempts(3);
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        let email = &validation.email;
+        workflow.start(&validation, now()).unwrap();
+        let code = workflow.mailer().last_secret(email).unwrap();
+        let wrong = if code == "000000" { "000001" } else { "000000" };
+
+        // Every guess is counted, even the ones checked at the same time
+        let barrier = Barrier::new(8);
+        let results: Vec<_> = thread::scope(|scope| {
+            let guesses: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        workflow.verify(email, wrong, now())
+                    })
+                })
+                .collect();
+            guesses.into_iter().map(|g| g.join().unwrap()).collect()
+        });
+        let invalid = results
+            .iter()
+            .filter(|result| matches!(result, Err(OwnershipError::InvalidSecret)))
+            .count();
+        assert!(invalid <= 3, "{:?}", results);
+        assert!(workflow.verify(email, &code, now()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_workflow() {
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        for digits in [0, 3, 20, 64] {
+            let workflow = workflow().with_secret_kind(SecretKind::Code { digits });
+            assert!(matches!(
+                workflow.start(&validation, now()),
+                Err(OwnershipError::InvalidWorkflow(_))
+            ));
+        }
+        let longest = workflow().with_secret_kind(SecretKind::Code { digits: 19 });
+        longest.start(&validation, now()).unwrap();
+        assert_eq!(longest.mailer().sent()[0].1.secret.len(), 19);
+
+        let workflow = workflow().with_ttl(Duration::MAX);
+        assert!(matches!(
+            workflow.start(&validation, now()),
+            Err(OwnershipError::InvalidWorkflow(_))
+        ));
+        assert!(workflow.mailer().sent().is_empty());
+    }
+
+    #[test]
+    fn test_link_token() {
+        let workflow = workflow().with_secret_kind(SecretKind::LinkToken);
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        workflow.start(&validation, now()).unwrap();
+        let message = &workflow.mailer().sent()[0].1;
+        assert_eq!(message.kind, SecretKind::LinkToken);
+        assert_eq!(message.secret.len(), 64);
+        assert_eq!(message.expires_at, now() + Duration::from_secs(15 * 60));
+        assert!(
+            workflow
+                .verify(&validation.email, &message.secret, now())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"123456", b"123456"));
+        assert!(!constant_time_eq(b"123456", b"123457"));
+        assert!(!constant_time_eq(b"123456", b"12345"));
+    }
+}
@@ -0,0 +1,162 @@
+use std::time::{Duration, SystemTime};
+
+use crate::dataset::Dataset;
+use crate::{Error, Validation};
+
+/// Default maximum age of a validation, a year
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+/// When a stored validation must be renewed by verifying the email again
+///
+/// A validation is renewed when it gets older than the maximum age, a year by
+/// default, or right away when the dataset now rejects its email, like a domain
+/// that moved to the stoplist or the abused list. The dataset is only looked up
+/// again when its hash changed since the validation.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use rswot::dataset::Dataset;
+/// use rswot::{ReverificationPolicy, ReverificationReason};
+///
+/// let validated_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_750_000_000);
+/// let validation = rswot::validate("lreilly@stanford.edu")
+///     .unwrap()
+///     .with_validated_at(validated_at);
+///
+/// let policy = ReverificationPolicy::default();
+/// let dataset = Dataset::embedded();
+/// let now = validated_at + Duration::from_secs(30 * 24 * 60 * 60);
+/// assert_eq!(policy.check(&validation, &dataset, now), None);
+/// let now = validated_at + Duration::from_secs(400 * 24 * 60 * 60);
+/// assert_eq!(
+///     policy.check(&validation, &dataset, now),
+///     Some(ReverificationReason::Expired)
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReverificationPolicy {
+    max_age: Duration,
+}
+
+/// Why a validation must be renewed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ReverificationReason {
+    /// The dataset now rejects the email
+    Rejected(Error),
+    /// The validation is older than the maximum age
+    Expired,
+    /// The validation has no [`validated_at`](Validation::validated_at)
+    Undated,
+}
+
+impl ReverificationPolicy {
+    /// Renew validations older than `max_age`
+    pub fn new(max_age: Duration) -> ReverificationPolicy {
+        ReverificationPolicy { max_age }
+    }
+
+    /// Maximum age of a validation
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    /// Whether a stored validation must be renewed at `now`, and why.
+    ///
+    /// A rejection by the dataset comes first, then the age of the validation.
+    pub fn check(
+        &self,
+        validation: &Validation,
+        dataset: &Dataset,
+        now: SystemTime,
+    ) -> Option<ReverificationReason> {
+        if validation.dataset_hash != Some(dataset.hash()) {
+            if let Err(error) = dataset.lookup(validation.email.domain()) {
+                return Some(ReverificationReason::Rejected(error));
+            }
+        }
+
+        let Some(validated_at) = validation.validated_at else {
+            return Some(ReverificationReason::Undated);
+        };
+        // A validation from the future, after a clock change, is not expired
+        let age = now.duration_since(validated_at).unwrap_or_default();
+        (age > self.max_age).then_some(ReverificationReason::Expired)
+    }
+}
+
+impl Default for ReverificationPolicy {
+    fn default() -> ReverificationPolicy {
+        ReverificationPolicy::new(DEFAULT_MAX_AGE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::DatasetHash;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn validated_at() -> SystemTime {
+        SystemTime::UNIX_EPOCH + 20_000 * DAY
+    }
+
+    #[test]
+    fn test_check_age() {
+        let dataset = Dataset::embedded();
+        let validation = crate::validate("lreilly@stanford.edu").unwrap();
+        let policy = ReverificationPolicy::new(30 * DAY);
+        assert_eq!(
+            policy.check(&validation, &dataset, validated_at()),
+            Some(ReverificationReason::Undated)
+        );
+
+        let validation = validation.with_validated_at(validated_at());
+        for (age, reason) in [
+            (Duration::ZERO, None),
+            (30 * DAY, None),
+            (31 * DAY, Some(ReverificationReason::Expired)),
+        ] {
+            let now = validated_at() + age;
+            assert_eq!(policy.check(&validation, &dataset, now), reason);
+        }
+        let now = validated_at() - DAY;
+        assert_eq!(policy.check(&validation, &dataset, now), None);
+    }
+
+    #[test]
+    fn test_check_rejected() {
+        // Validated against another dataset, before the domain was stoplisted
+        let validation = crate::validate("lee@cqut.edu.cn").unwrap();
+        let validation = Validation {
+            email: crate::Email::parse("lee@2011.cqut.edu.cn").unwrap(),
+            dataset_hash: Some("0".parse::<DatasetHash>().unwrap()),
+            ..validation
+        }
+        .with_validated_at(validated_at());
+
+        let policy = ReverificationPolicy::default();
+        assert_eq!(
+            policy.check(&validation, &Dataset::embedded(), validated_at()),
+            Some(ReverificationReason::Rejected(Error::Stop))
+        );
+
+        // The dataset is not looked up again if it did not change
+        let validation = Validation {
+            dataset_hash: Some(Dataset::embedded().hash()),
+            ..validation
+        };
+        assert_eq!(
+            policy.check(&validation, &Dataset::embedded(), validated_at()),
+            None
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// Local parts of role accounts, in several languages, without separators
+const BUILTIN_NAMES: &[&str] = &[
+    // English
+    "abuse",
+    "accounting",
+    "accounts",
+    "admin",
+    "administration",
+    "administrator",
+    "admissions",
+    "alumni",
+    "billing",
+    "bursar",
+    "careers",
+    "contact",
+    "donotreply",
+    "enquiries",
+    "events",
+    "facilities",
+    "feedback",
+    "finance",
+    "hello",
+    "help",
+    "helpdesk",
+    "hostmaster",
+    "hr",
+    "info",
+    "information",
+    "it",
+    "itsupport",
+    "jobs",
+    "library",
+    "mailerdaemon",
+    "marketing",
+    "media",
+    "news",
+    "newsletter",
+    "noc",
+    "noreply",
+    "office",
+    "payroll",
+    "postmaster",
+    "press",
+    "privacy",
+    "reception",
+    "recruitment",
+    "registrar",
+    "root",
+    "sales",
+    "secretary",
+    "security",
+    "support",
+    "sysadmin",
+    "webmaster",
+    // French
+    "accueil",
+    "bibliotheque",
+    "communication",
+    "comptabilite",
+    "informatique",
+    "inscriptions",
+    "nepasrepondre",
+    "rh",
+    "scolarite",
+    "secretariat",
+    // Spanish and Portuguese
+    "admision",
+    "admisiones",
+    "biblioteca",
+    "comunicacion",
+    "contacto",
+    "contato",
+    "decanato",
+    "informacion",
+    "naoresponda",
+    "noresponder",
+    "recepcion",
+    "rectorado",
+    "reitoria",
+    "secretaria",
+    "soporte",
+    "suporte",
+    "tesoreria",
+    // German and Dutch
+    "bibliotheek",
+    "bibliothek",
+    "dekanat",
+    "keineantwort",
+    "kontakt",
+    "poststelle",
+    "pressestelle",
+    "rechenzentrum",
+    "secretariaat",
+    "sekretariat",
+    "verwaltung",
+    // Italian
+    "amministrazione",
+    "informazioni",
+    "protocollo",
+    "segreteria",
+    // Polish and Turkish
+    "bilgi",
+    "biuro",
+    "dziekanat",
+    "kutuphane",
+    "ogrenciisleri",
+    "rektorat",
+];
+
+/// Length of the shortest name that can be made of several words, shorter ones
+/// would match initials
+const MIN_JOINED_LEN: usize = 3;
+
+static BUILTIN: LazyLock<RoleAccounts> =
+    LazyLock::new(|| RoleAccounts::empty().with_names(BUILTIN_NAMES.iter().copied()));
+
+/// Classifier of role accounts, like `admin` or `noreply`, that are not
+/// individuals
+///
+/// A local part is a role account when its first words, split on separators
+/// (`.`, `-` and `_`), are one of the names, like `info.cs` or `no-reply`.
+/// Words are only matched whole, so `lee.info` or `informal` are not `info`.
+/// Trailing digits and `+` tags are ignored, so `no-reply+alerts` is `noreply`.
+///
+/// Names of one or two letters, like `hr` or `it`, must be a single word, so
+/// initials like `h.r` or `i.t` are not role accounts.
+///
+/// The default classifier has a built-in list in several languages, that can be
+/// extended or trimmed.
+///
+/// # Examples
+///
+/// ```
+/// use rswot::RoleAccounts;
+///
+/// let role_accounts = RoleAccounts::default()
+///     .with_names(["studentunion"])
+///     .without_names(["alumni"]);
+/// assert!(role_accounts.is_role_account("no-reply"));
+/// assert!(role_accounts.is_role_account("student.union"));
+/// assert!(!role_accounts.is_role_account("alumni"));
+/// assert!(!role_accounts.is_role_account("lreilly"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleAccounts {
+    names: HashSet<String>,
+    /// Length of the longest name, in characters, so longer prefixes of local
+    /// parts are not compared
+    max_len: usize,
+}
+
+impl RoleAccounts {
+    /// Built-in classifier
+    pub fn builtin() -> &'static RoleAccounts {
+        &BUILTIN
+    }
+
+    /// Classifier without any name
+    pub fn empty() -> RoleAccounts {
+        RoleAccounts {
+            names: HashSet::new(),
+            max_len: 0,
+        }
+    }
+
+    /// Add names of role accounts
+    pub fn with_names<'a>(mut self, names: impl IntoIterator<Item = &'a str>) -> RoleAccounts {
+        self.names.extend(
+            names
+                .into_iter()
+                .map(compact)
+                .filter(|name| !name.is_empty()),
+        );
+        self.update_max_len();
+        self
+    }
+
+    /// Remove names of role accounts, like built-in ones
+    pub fn without_names<'a>(mut self, names: impl IntoIterator<Item = &'a str>) -> RoleAccounts {
+        for name in names {
+            self.names.remove(&compact(name));
+        }
+        self.update_max_len();
+        self
+    }
+
+    fn update_max_len(&mut self) {
+        self.max_len = self
+            .names
+            .iter()
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or(0);
+    }
+
+    /// Whether the local part of a normalized email is a role account
+    ///
+    /// Local parts are compared with the names as they are, without
+    /// allocating.
+    pub fn is_role_account(&self, user: &str) -> bool {
+        let user = user.split_once('+').map_or(user, |(user, _)| user);
+        // Ends of the prefixes of one word, two words and so on
+        let ends = user
+            .match_indices(is_separator)
+            .map(|(i, _)| i)
+            .chain([user.len()]);
+        for (count, end) in ends.enumerate() {
+            let prefix =
+                user[..end].trim_end_matches(|c: char| is_separator(c) || c.is_ascii_digit());
+            let len = prefix.chars().filter(|c| !is_separator(*c)).count();
+            if len > self.max_len {
+                return false;
+            }
+            if (count == 0 || len >= MIN_JOINED_LEN) && self.contains(prefix) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether a prefix of a local part, without trailing digits, is one of
+    /// the names once compacted
+    fn contains(&self, prefix: &str) -> bool {
+        if !prefix.contains(is_separator) && !prefix.contains(char::is_uppercase) {
+            return self.names.contains(prefix);
+        }
+        let compacted = || {
+            prefix
+                .chars()
+                .filter(|c| !is_separator(*c))
+                .flat_map(char::to_lowercase)
+        };
+        self.names.iter().any(|name| name.chars().eq(compacted()))
+    }
+}
+
+impl Default for RoleAccounts {
+    fn default() -> RoleAccounts {
+        RoleAccounts::builtin().clone()
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '.' | '-' | '_')
+}
+
+/// Lowercase name without separators nor trailing digits
+fn compact(name: &str) -> String {
+    name.chars()
+        .filter(|c| !is_separator(*c))
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .trim_end_matches(|c: char| c.is_ascii_digit())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_role_account() {
+        let role_accounts = RoleAccounts::builtin();
+        let tests = [
+            ("admin", true),
+            ("no-reply", true),
+            ("no_reply+alerts", true),
+            ("do.not.reply", true),
+            ("info.cs", true),
+            ("library2", true),
+            ("secretaria", true),
+            ("sekretariat-informatik", true),
+            ("lreilly", false),
+            ("lee.info", false),
+            ("mark.sales", false),
+            ("administrator.of.things", true),
+            ("no.reply.cs", true),
+            ("hr", true),
+            ("hr.office", true),
+            ("it-support", true),
+            // Initials are people, not the short built-in names
+            ("h.r", false),
+            ("i.t", false),
+            ("r.h", false),
+            ("h_r2", false),
+            // Names are matched on whole words
+            ("informal", false),
+            ("hristo", false),
+            ("", false),
+        ];
+        for (user, is_role) in tests {
+            assert_eq!(role_accounts.is_role_account(user), is_role, "{}", user);
+        }
+    }
+
+    #[test]
+    fn test_with_names() {
+        let role_accounts = RoleAccounts::empty().with_names(["Student-Union"]);
+        assert!(role_accounts.is_role_account("studentunion"));
+        assert!(role_accounts.is_role_account("student.union"));
+        assert!(!role_accounts.is_role_account("admin"));
+
+        let role_accounts = RoleAccounts::default().without_names(["it"]);
+        assert!(!role_accounts.is_role_account("it"));
+        assert!(role_accounts.is_role_account("itsupport"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Corrections for mistyped academic domains.
+//!
+//! A domain that is not academic is compared with the institution domains and
+//! academic suffixes of the dataset, with an edit distance where typing mistakes
+//! are cheap:
+//!
+//! - a key next to the intended one on a QWERTY keyboard, like `ac.xa`;
+//! - a letter typed twice or once instead of twice, like `ugr.ess`;
+//! - two adjacent letters swapped, like `stanford.eud`;
+//! - the letters of the top-level label scrambled, like `stanford.ude`.
+//!
+//! Leading labels are kept, so `cs.stanfrod.edu` is corrected to `cs.stanford.edu`.
+
+use std::collections::HashMap;
+
+use crate::index::{Costs, DomainIndex, distance};
+
+/// Closest corrections of a normalized domain among the domains of the index
+/// that are valid, sorted
+pub(crate) fn find(
+    index: &DomainIndex,
+    domain: &str,
+    is_valid: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let labels: Vec<&str> = domain.split('.').collect();
+    let mut corrections: HashMap<String, u32> = HashMap::new();
+
+    for start in 0..labels.len() {
+        let prefix = &labels[..start];
+        let tail = labels[start..].join(".");
+        let max = max_cost(&tail);
+        let lengths = tail.len().saturating_sub(max as usize)..=tail.len() + max as usize;
+        for len in lengths {
+            for known in index.with_length(labels.len() - start, len) {
+                if known.is_suffix && prefix.is_empty() {
+                    continue;
+                }
+                let Some(cost) = distance(&tail, &known.domain, Costs::Typing, max)
+                    .or_else(|| scrambled_top_label(&tail, &known.domain).then_some(1))
+                else {
+                    continue;
+                };
+                if cost == 0 {
+                    continue;
+                }
+                let correction = prefix
+                    .iter()
+                    .copied()
+                    .chain([known.domain.as_str()])
+                    .collect::<Vec<_>>()
+                    .join(".");
+                if let Some(best) = corrections.get_mut(&correction) {
+                    *best = (*best).min(cost);
+                } else if is_valid(&correction) {
+                    corrections.insert(correction, cost);
+                }
+            }
+        }
+    }
+
+    let Some(&closest) = corrections.values().min() else {
+        return Vec::new();
+    };
+    let mut corrections: Vec<_> = corrections
+        .into_iter()
+        .filter_map(|(domain, cost)| (cost == closest).then_some(domain))
+        .collect();
+    corrections.sort_unstable();
+    corrections
+}
+
+/// Maximum cost of a correction, in half edits
+fn max_cost(domain: &str) -> u32 {
+    match domain.len() {
+        0..8 => 1,
+        8..16 => 2,
+        _ => 3,
+    }
+}
+
+/// Whether two domains only differ by the order of the letters of their
+/// top-level label
+fn scrambled_top_label(a: &str, b: &str) -> bool {
+    let (Some((a_rest, a_top)), Some((b_rest, b_top))) = (a.rsplit_once('.'), b.rsplit_once('.'))
+    else {
+        return false;
+    };
+    let sorted = |label: &str| {
+        let mut letters = label.as_bytes().to_vec();
+        letters.sort_unstable();
+        letters
+    };
+    a_rest == b_rest && a_top != b_top && sorted(a_top) == sorted(b_top)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let index = DomainIndex::new(
+            ["stanford.edu", "ugr.es", "uct.ac.za", "mit.edu", "kit.edu"]
+                .into_iter()
+                .map(String::from),
+            ["ac.za", "ac.ma"].into_iter().map(String::from),
+        );
+        let tests: [(&str, &[&str]); 9] = [
+            ("stanford.eud", &["stanford.edu"]),
+            ("cs.stanfrod.edu", &["cs.stanford.edu"]),
+            ("stanford.ude", &["stanford.edu"]),
+            ("ugr.ess", &["ugr.es"]),
+            ("uct.ac.xa", &["uct.ac.za"]),
+            ("wits.ac.xa", &["wits.ac.za"]),
+            // Only the closest corrections are kept
+            ("jit.edu", &["kit.edu", "mit.edu"]),
+            // Academic suffixes alone are not domains of institutions
+            ("ac.xa", &[]),
+            ("gmail.com", &[]),
+        ];
+        for (domain, corrections) in tests {
+            assert_eq!(find(&index, domain, |_| true), corrections, "{}", domain);
+        }
+
+        // Invalid corrections do not hide valid ones
+        assert_eq!(
+            find(&index, "jit.edu", |domain| domain != "kit.edu"),
+            ["mit.edu"]
+        );
+    }
+}
@@ -1,21 +1,38 @@
-use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 /// Top-level domain
-#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct Tld(Cow<'static, str>);
+#[derive(Clone)]
+pub struct Tld(Repr);
+
+/// Top-level domains of loaded datasets are shared, so cloning them is as cheap
+/// as cloning embedded ones
+#[derive(Clone)]
+enum Repr {
+    Static(&'static str),
+    Shared(Arc<str>),
+}
 
 impl Tld {
     pub const fn new(tld: &'static str) -> Tld {
-        Tld(Cow::Borrowed(tld))
+        Tld(Repr::Static(tld))
     }
 
     pub fn new_owned(tld: String) -> Tld {
-        Tld(Cow::Owned(tld))
+        Tld::shared(tld.into())
+    }
+
+    pub(crate) fn shared(tld: Arc<str>) -> Tld {
+        Tld(Repr::Shared(tld))
     }
 
     pub fn as_str(&self) -> &str {
-        self.0.as_ref()
+        match &self.0 {
+            Repr::Static(tld) => tld,
+            Repr::Shared(tld) => tld,
+        }
     }
 }
 
@@ -271,12 +288,51 @@ impl Tld {
 
 impl AsRef<str> for Tld {
     fn as_ref(&self) -> &str {
-        self.0.as_ref()
+        self.as_str()
     }
 }
 
 impl fmt::Display for Tld {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(f, "{}", self.0.as_ref())
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Debug for Tld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_tuple("Tld").field(&self.as_str()).finish()
+    }
+}
+
+impl PartialEq for Tld {
+    fn eq(&self, other: &Tld) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Tld {}
+
+impl Hash for Tld {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialOrd for Tld {
+    fn partial_cmp(&self, other: &Tld) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Tld {
+    fn cmp(&self, other: &Tld) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Tld {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct("Tld", self.as_str())
     }
 }
@@ -1,30 +1,120 @@
-use crate::{Email, Tld};
+use std::time::SystemTime;
+
+use crate::dataset::{DatasetHash, InstitutionNames};
+use crate::{AffiliationHint, Email, RoleAccounts, Tld};
 
 /// Result of validation
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Validation {
     /// Email address that was validated
     pub email: Email,
     /// Top-level domain of email
     pub tld: Tld,
     /// Names of the institution that the email is from
-    pub institution_names: Option<Vec<String>>,
+    ///
+    /// Empty if the institution is known but names are not embedded,
+    /// see [`dataset::HAS_INSTITUTION_NAMES`](crate::dataset::HAS_INSTITUTION_NAMES).
+    pub institution_names: Option<InstitutionNames>,
+    /// Affiliation hinted by the labels of the domain, like `students`
+    pub affiliation: AffiliationHint,
+    /// Whether the email is a role account like `admin@` rather than an
+    /// individual, see [`RoleAccounts`]
+    pub role_account: bool,
+    /// Hash of the dataset the email was validated against
+    pub dataset_hash: Option<DatasetHash>,
+    /// When the email was validated, if stamped with
+    /// [`with_validated_at`](Validation::with_validated_at)
+    ///
+    /// Serialized as seconds since the Unix epoch, and skipped if `None`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "serialize_unix_time"
+        )
+    )]
+    pub validated_at: Option<SystemTime>,
+}
+
+/// Result of validation of a domain
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DomainValidation {
+    /// Top-level domain
+    pub tld: Tld,
+    /// Names of the institution that the domain belongs to
+    ///
+    /// `None` if the domain is only under an academic suffix like `ac.za`.
+    pub institution_names: Option<InstitutionNames>,
+    /// Affiliation hinted by the labels of the domain, like `students`
+    pub affiliation: AffiliationHint,
+    /// Hash of the dataset the domain was validated against
+    pub dataset_hash: Option<DatasetHash>,
 }
 
 impl Validation {
-    pub(crate) fn new(email: Email) -> Validation {
-        let tld = email.tld();
+    pub(crate) fn new(email: Email, domain: DomainValidation) -> Validation {
         Validation {
+            role_account: RoleAccounts::builtin().is_role_account(email.user()),
             email,
+            tld: domain.tld,
+            institution_names: domain.institution_names,
+            affiliation: domain.affiliation,
+            dataset_hash: domain.dataset_hash,
+            validated_at: None,
+        }
+    }
+
+    /// Stamp the validation with the time it was made, to renew it later with
+    /// a [`ReverificationPolicy`](crate::ReverificationPolicy)
+    pub fn with_validated_at(self, validated_at: SystemTime) -> Validation {
+        Validation {
+            validated_at: Some(validated_at),
+            ..self
+        }
+    }
+}
+
+impl DomainValidation {
+    pub(crate) fn new(tld: Tld) -> DomainValidation {
+        DomainValidation {
             tld,
             institution_names: None,
+            affiliation: AffiliationHint::Unknown,
+            dataset_hash: None,
         }
     }
 
-    pub(crate) fn with_institutions(self, institution_names: Vec<String>) -> Validation {
-        Validation {
+    pub(crate) fn with_institutions(self, institution_names: InstitutionNames) -> DomainValidation {
+        DomainValidation {
             institution_names: Some(institution_names),
             ..self
         }
     }
+
+    pub(crate) fn with_affiliation(self, affiliation: AffiliationHint) -> DomainValidation {
+        DomainValidation {
+            affiliation,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_dataset_hash(self, dataset_hash: DatasetHash) -> DomainValidation {
+        DomainValidation {
+            dataset_hash: Some(dataset_hash),
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serialize_unix_time<S: serde::Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let seconds = time
+        .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs());
+    serde::Serialize::serialize(&seconds, serializer)
 }
@@ -0,0 +1,541 @@
+use std::fmt;
+use std::sync::RwLock;
+use std::time::Instant;
+
+use crate::cache::{CacheStats, DomainCache};
+use crate::dataset::{self, Dataset, DatasetHash};
+use crate::dns::{self, Resolver};
+#[cfg(feature = "idn")]
+use crate::idn::{self, ScriptPolicy};
+use crate::{
+    AbuseMode, CohortPolicy, Decision, DecisionObserver, DomainValidation, Email, Error,
+    MatchSource, Result, RoleAccounts, Validation, email, instrument, observer,
+};
+
+/// Configurable validator, shared between threads.
+///
+/// Validates against a dataset that can be swapped while in use, can cache
+/// the decision for each domain and check that domains have a mail server.
+///
+/// # Examples
+///
+/// ```
+/// use rswot::Validator;
+/// use rswot::dataset::Dataset;
+///
+/// let validator = Validator::new(Dataset::embedded()).with_cache(1024);
+/// assert!(validator.validate("lreilly@stanford.edu").is_ok());
+/// assert!(validator.validate("john@stanford.edu").is_ok());
+/// assert_eq!(validator.cache_stats().unwrap().hits, 1);
+/// ```
+#[derive(Default)]
+pub struct Validator {
+    dataset: RwLock<Dataset>,
+    cache: Option<DomainCache>,
+    resolver: Option<Box<dyn Resolver + Send + Sync>>,
+    abuse_mode: AbuseMode,
+    impersonation_check: bool,
+    role_accounts: Option<RoleAccounts>,
+    role_account_check: bool,
+    cohort_policy: Option<CohortPolicy>,
+    #[cfg(feature = "idn")]
+    script_policy: Option<ScriptPolicy>,
+    observers: Vec<Box<dyn DecisionObserver>>,
+}
+
+/// What a decision on a domain was made from, for observers
+#[derive(Default)]
+struct Trace {
+    match_source: Option<MatchSource>,
+    dataset_hash: Option<DatasetHash>,
+}
+
+impl Validator {
+    /// Validator for a dataset, without cache
+    pub fn new(dataset: Dataset) -> Validator {
+        Validator {
+            dataset: RwLock::new(dataset),
+            cache: None,
+            resolver: None,
+            abuse_mode: AbuseMode::Distinct,
+            impersonation_check: false,
+            role_accounts: None,
+            role_account_check: false,
+            cohort_policy: None,
+            #[cfg(feature = "idn")]
+            script_policy: None,
+            observers: Vec::new(),
+        }
+    }
+
+    /// Cache the decisions of up to `capacity` domains, evicted with the CLOCK
+    /// algorithm: domains not looked up since the last sweep go first.
+    ///
+    /// A capacity of 0 disables the cache.
+    pub fn with_cache(self, capacity: usize) -> Validator {
+        Validator {
+            cache: (capacity > 0).then(|| DomainCache::new(capacity)),
+            ..self
+        }
+    }
+
+    /// Reject emails whose domain has no mail server with
+    /// [`Error::NoMailServer`], see [`dns::check_mail_server`].
+    ///
+    /// Mail servers are checked after the dataset and are not cached.
+    pub fn with_resolver(self, resolver: impl Resolver + Send + Sync + 'static) -> Validator {
+        Validator {
+            resolver: Some(Box::new(resolver)),
+            ..self
+        }
+    }
+
+    /// Set how the abuse categories are reported, distinct by default
+    pub fn with_abuse_mode(self, abuse_mode: AbuseMode) -> Validator {
+        Validator { abuse_mode, ..self }
+    }
+
+    /// Reject domains that are not academic but look like the domain of an
+    /// institution with [`Error::SuspectedImpersonation`], instead of
+    /// [`Error::NotAcademic`], see [`Dataset::impersonated_institution`].
+    pub fn with_impersonation_check(self) -> Validator {
+        Validator {
+            impersonation_check: true,
+            ..self
+        }
+    }
+
+    /// Classify role accounts with another classifier than
+    /// [`RoleAccounts::builtin`], like one extended with local names
+    pub fn with_role_accounts(self, role_accounts: RoleAccounts) -> Validator {
+        Validator {
+            role_accounts: Some(role_accounts),
+            ..self
+        }
+    }
+
+    /// Reject role accounts like `admin@` with [`Error::RoleAccount`], instead
+    /// of only reporting them in [`Validation::role_account`].
+    ///
+    /// Role accounts are checked after the domain.
+    pub fn with_role_account_check(self) -> Validator {
+        Validator {
+            role_account_check: true,
+            ..self
+        }
+    }
+
+    /// Reject cohort subdomains of institutions, like `2011.cqut.edu.cn`, that
+    /// the policy does not accept with [`Error::Cohort`], see
+    /// [`CohortPolicy::check`].
+    ///
+    /// Cohorts are checked after the dataset and are not cached.
+    pub fn with_cohort_policy(self, cohort_policy: CohortPolicy) -> Validator {
+        Validator {
+            cohort_policy: Some(cohort_policy),
+            ..self
+        }
+    }
+
+    /// Reject internationalized domains that mix scripts, use a script the
+    /// policy does not allow or look like an academic domain, see
+    /// [`idn::check_domain`].
+    ///
+    /// Domains are checked before the dataset and are not cached.
+    #[cfg(feature = "idn")]
+    pub fn with_idn_check(self, script_policy: ScriptPolicy) -> Validator {
+        Validator {
+            script_policy: Some(script_policy),
+            ..self
+        }
+    }
+
+    /// Invoke an observer with every decision on an email, in the order
+    /// observers are added, see [`DecisionObserver`].
+    ///
+    /// Domains validated with [`validate_domain`](Validator::validate_domain)
+    /// are not observed.
+    pub fn with_observer(mut self, observer: impl DecisionObserver + 'static) -> Validator {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    /// Dataset currently in use
+    pub fn dataset(&self) -> Dataset {
+        self.dataset.read().unwrap().clone()
+    }
+
+    /// Swap the dataset in use and drop the cached decisions.
+    ///
+    /// Validations in flight finish with the previous dataset, and none of
+    /// their decisions is cached past the swap.
+    pub fn set_dataset(&self, dataset: Dataset) {
+        let mut current = self.dataset.write().unwrap();
+        *current = dataset;
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Statistics of the cache, `None` without cache
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(DomainCache::stats)
+    }
+
+    /// Validate an email address, see [`validate`](crate::validate).
+    pub fn validate<T>(&self, email: T) -> Result<Validation>
+    where
+        T: TryInto<Email>,
+        T::Error: Into<Error>,
+    {
+        if self.observers.is_empty() {
+            let email = email.try_into().map_err(Into::into)?;
+            return self.validate_email(email, &mut Trace::default());
+        }
+
+        let start = Instant::now();
+        let (email, input): (Result<Email>, _) =
+            observer::capture_input(|| email.try_into().map_err(Into::into));
+        let mut trace = Trace::default();
+        let outcome = email
+            .clone()
+            .and_then(|email| self.validate_email(email, &mut trace));
+        let decision = Decision {
+            input: input
+                .as_deref()
+                .or_else(|| email.as_ref().ok().map(Email::as_str)),
+            email: email.as_ref().ok(),
+            outcome: &outcome,
+            match_source: trace.match_source,
+            dataset_version: dataset::VERSION,
+            dataset_hash: trace.dataset_hash,
+            duration: start.elapsed(),
+        };
+        for observer in &self.observers {
+            observer.observe(&decision);
+        }
+        outcome
+    }
+
+    fn validate_email(&self, email: Email, trace: &mut Trace) -> Result<Validation> {
+        let _span = instrument::validate_span(&email);
+        let outcome = self.decide_email(email, trace);
+        instrument::decision(&outcome);
+        outcome
+    }
+
+    fn decide_email(&self, email: Email, trace: &mut Trace) -> Result<Validation> {
+        let domain = self.decide(email.domain(), trace)?;
+        let mut validation = Validation::new(email, domain);
+        if let Some(role_accounts) = &self.role_accounts {
+            validation.role_account = role_accounts.is_role_account(validation.email.user());
+        }
+        if self.role_account_check && validation.role_account {
+            return Err(Error::RoleAccount);
+        }
+        Ok(validation)
+    }
+
+    /// Validate a domain name, see [`validate_domain`](crate::validate_domain).
+    pub fn validate_domain(&self, domain: &str) -> Result<DomainValidation> {
+        self.decide(&email::parse_domain(domain)?, &mut Trace::default())
+    }
+
+    fn decide(&self, domain: &str, trace: &mut Trace) -> Result<DomainValidation> {
+        #[cfg(feature = "idn")]
+        if let Some(script_policy) = &self.script_policy {
+            idn::check_domain(&self.dataset.read().unwrap(), domain, script_policy)?;
+        }
+        let validation = self
+            .lookup(domain, trace)
+            .map_err(|error| self.abuse_mode.apply(error))?;
+        if let Some(cohort_policy) = &self.cohort_policy {
+            cohort_policy.check(domain)?;
+        }
+        if let Some(resolver) = &self.resolver {
+            dns::check_domain(resolver.as_ref(), domain)?;
+        }
+        Ok(validation)
+    }
+
+    fn lookup(&self, domain: &str, trace: &mut Trace) -> Result<DomainValidation> {
+        // The read lock is held until the decision is cached, so it can't be
+        // cached after the dataset it comes from is swapped
+        let dataset = self.dataset.read().unwrap();
+        trace.dataset_hash = Some(dataset.hash());
+        let decision = match &self.cache {
+            Some(cache) => cache.get(domain).unwrap_or_else(|| {
+                let decision = self.lookup_dataset(&dataset, domain);
+                cache.insert(domain, decision.clone());
+                decision
+            }),
+            None => self.lookup_dataset(&dataset, domain),
+        };
+        trace.match_source = MatchSource::of(&decision);
+        decision
+    }
+
+    fn lookup_dataset(&self, dataset: &Dataset, domain: &str) -> Result<DomainValidation> {
+        match dataset.lookup(domain) {
+            Err(Error::NotAcademic) if self.impersonation_check => {
+                Err(match dataset.find_impersonated(domain) {
+                    Some(target) => Error::SuspectedImpersonation { target },
+                    None => Error::NotAcademic,
+                })
+            }
+            decision => decision,
+        }
+    }
+}
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Validator");
+        debug
+            .field("dataset", &self.dataset)
+            .field("cache", &self.cache)
+            .field("resolver", &self.resolver.is_some())
+            .field("abuse_mode", &self.abuse_mode)
+            .field("impersonation_check", &self.impersonation_check)
+            .field("role_accounts", &self.role_accounts)
+            .field("role_account_check", &self.role_account_check)
+            .field("cohort_policy", &self.cohort_policy);
+        #[cfg(feature = "idn")]
+        debug.field("script_policy", &self.script_policy);
+        debug.field("observers", &self.observers.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn domains_dir() -> &'static str {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/domains")
+    }
+
+    #[test]
+    fn test_validate_with_cache() {
+        let validator = Validator::default().with_cache(16);
+        for email in ["lee@gmail.com", "LEE@GMAIL.COM", "john@Stanford.edu"] {
+            assert_eq!(validator.validate(email), crate::validate(email));
+        }
+        assert_eq!(
+            validator.validate_domain("Stanford.edu"),
+            crate::validate_domain("stanford.edu")
+        );
+        assert_eq!(validator.validate("the"), Err(Error::InvalidEmail));
+
+        let stats = validator.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.len), (2, 2, 2));
+    }
+
+    #[test]
+    fn test_without_cache() {
+        let validator = Validator::default().with_cache(0);
+        assert!(validator.validate("lreilly@stanford.edu").is_ok());
+        assert_eq!(validator.cache_stats(), None);
+    }
+
+    #[test]
+    fn test_with_resolver() {
+        let resolver = dns::InMemoryResolver::new().with_mx("stanford.edu", "mx.stanford.edu");
+        let validator = Validator::default().with_cache(16).with_resolver(resolver);
+        assert!(validator.validate("lreilly@stanford.edu").is_ok());
+        assert_eq!(validator.validate("lee@ugr.es"), Err(Error::NoMailServer));
+        assert_eq!(
+            validator.validate_domain("ugr.es"),
+            Err(Error::NoMailServer)
+        );
+        // The dataset decides first
+        assert_eq!(
+            validator.validate("lee@gmail.com"),
+            Err(Error::FreeMailProvider)
+        );
+    }
+
+    #[test]
+    fn test_with_abuse_mode() {
+        let validator = Validator::default()
+            .with_cache(16)
+            .with_abuse_mode(AbuseMode::Combined);
+        for email in ["lee@gmail.com", "lee@mailinator.com", "imposter@si.edu"] {
+            assert_eq!(validator.validate(email), Err(Error::Abuse));
+        }
+        assert_eq!(validator.validate_domain("gmail.com"), Err(Error::Abuse));
+        assert_eq!(
+            validator.validate("lee@leerilly.net"),
+            Err(Error::NotAcademic)
+        );
+    }
+
+    #[test]
+    fn test_with_impersonation_check() {
+        let validator = Validator::default()
+            .with_cache(16)
+            .with_impersonation_check();
+        for _ in 0..2 {
+            assert_eq!(
+                validator.validate("lee@harvard-edu.org"),
+                Err(Error::SuspectedImpersonation {
+                    target: "harvard.edu".to_string()
+                })
+            );
+        }
+        assert_eq!(
+            validator.validate("lee@leerilly.net"),
+            Err(Error::NotAcademic)
+        );
+        assert!(validator.validate("lreilly@stanford.edu").is_ok());
+        assert_eq!(
+            Validator::default().validate("lee@harvard-edu.org"),
+            Err(Error::NotAcademic)
+        );
+    }
+
+    #[test]
+    fn test_with_role_accounts() {
+        let validator = Validator::default();
+        assert!(
+            validator
+                .validate("admin@stanford.edu")
+                .unwrap()
+                .role_account
+        );
+        assert!(
+            !validator
+                .validate("lreilly@stanford.edu")
+                .unwrap()
+                .role_account
+        );
+
+        let validator = Validator::default()
+            .with_role_accounts(RoleAccounts::default().with_names(["studentunion"]))
+            .with_role_account_check();
+        assert_eq!(
+            validator.validate("student-union@stanford.edu"),
+            Err(Error::RoleAccount)
+        );
+        assert_eq!(
+            validator.validate("no-reply@stanford.edu"),
+            Err(Error::RoleAccount)
+        );
+        // The domain is checked first
+        assert_eq!(
+            validator.validate("admin@gmail.com"),
+            Err(Error::FreeMailProvider)
+        );
+        assert!(validator.validate_domain("stanford.edu").is_ok());
+        assert!(validator.validate("lreilly@stanford.edu").is_ok());
+    }
+
+    #[test]
+    fn test_with_cohort_policy() {
+        let email = "lee@class2019.stanford.edu";
+        assert!(Validator::default().validate(email).is_ok());
+
+        let validator = Validator::default()
+            .with_cache(16)
+            .with_cohort_policy(CohortPolicy::within(2026, 4));
+        for _ in 0..2 {
+            assert_eq!(validator.validate(email), Err(Error::Cohort { year: 2019 }));
+        }
+        assert!(validator.validate("lee@class2024.stanford.edu").is_ok());
+        assert!(validator.validate("lreilly@stanford.edu").is_ok());
+        // The dataset decides first
+        assert_eq!(
+            validator.validate_domain("2011.cqut.edu.cn"),
+            Err(Error::Stop)
+        );
+        assert_eq!(
+            validator.validate_domain("2019.cqut.edu.cn"),
+            Err(Error::Cohort { year: 2019 })
+        );
+    }
+
+    #[test]
+    fn test_with_observer() {
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl DecisionObserver for Recorder {
+            fn observe(&self, decision: &Decision<'_>) {
+                assert_eq!(decision.dataset_version, dataset::VERSION);
+                assert_eq!(decision.dataset_hash.is_some(), decision.email.is_some());
+                self.0.lock().unwrap().push(format!(
+                    "{:?} {:?} {:?} {:?}",
+                    decision.input,
+                    decision.email.map(Email::as_str),
+                    decision.outcome.as_ref().err(),
+                    decision.match_source,
+                ));
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let validator = Validator::default()
+            .with_cache(16)
+            .with_abuse_mode(AbuseMode::Combined)
+            .with_cohort_policy(CohortPolicy::RejectAll)
+            .with_observer(recorder.clone());
+        for email in [
+            "Lee@Stanford.edu",
+            "lee@stanford.edu",
+            "lee@gmail.com",
+            "lee@class2019.stanford.edu",
+            "lee",
+        ] {
+            let _ = validator.validate(email);
+        }
+        let _ = validator.validate(" Lee@@x ".to_string());
+        let _ = validator.validate(Email::parse("Lee@gmail.com").unwrap());
+        assert!(validator.validate_domain("stanford.edu").is_ok());
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                r#"Some("Lee@Stanford.edu") Some("lee@stanford.edu") None Some(Institution)"#,
+                // From the cache
+                r#"Some("lee@stanford.edu") Some("lee@stanford.edu") None Some(Institution)"#,
+                // The list is kept when abuse categories are combined
+                r#"Some("lee@gmail.com") Some("lee@gmail.com") Some(Abuse) Some(FreeMail)"#,
+                r#"Some("lee@class2019.stanford.edu") Some("lee@class2019.stanford.edu") Some(Cohort { year: 2019 }) Some(Institution)"#,
+                r#"Some("lee") None Some(InvalidEmail) None"#,
+                r#"Some(" Lee@@x ") None Some(InvalidEmail) None"#,
+                // Emails are formatted
+                r#"Some("lee@gmail.com") Some("lee@gmail.com") Some(Abuse) Some(FreeMail)"#,
+            ]
+        );
+    }
+
+    #[cfg(feature = "idn")]
+    #[test]
+    fn test_with_idn_check() {
+        // "hаrvard.ac.il" with a Cyrillic "а", under an academic suffix
+        let email = "lee@xn--hrvard-3nf.ac.il";
+        assert!(Validator::default().validate(email).is_ok());
+
+        let validator = Validator::default()
+            .with_cache(16)
+            .with_idn_check(ScriptPolicy::default());
+        for _ in 0..2 {
+            assert_eq!(validator.validate(email), Err(Error::MixedScript));
+        }
+        assert!(validator.validate("lreilly@stanford.edu").is_ok());
+    }
+
+    #[test]
+    fn test_set_dataset_clears_cache() {
+        let validator = Validator::default().with_cache(16);
+        assert!(validator.validate("lreilly@stanford.edu").is_ok());
+        assert_eq!(validator.cache_stats().unwrap().len, 1);
+
+        validator.set_dataset(Dataset::load(domains_dir()).unwrap());
+        assert!(!validator.dataset().is_embedded());
+        assert_eq!(validator.cache_stats().unwrap().len, 0);
+        assert!(validator.validate("lreilly@stanford.edu").is_ok());
+        assert_eq!(validator.cache_stats().unwrap().misses, 2);
+    }
+}
@@ -0,0 +1,64 @@
+//! Allocations made by validations, counted with a global allocator, in their
+//! own test binary so other tests don't count.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Allocations made by `f` on this thread
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let value = f();
+    let after = ALLOCATIONS.with(Cell::get);
+    drop(value);
+    after - before
+}
+
+#[test]
+fn test_validate_allocates_once() {
+    for email in [
+        "lreilly@stanford.edu",
+        "lreilly@students.uonbi.ac.ke",
+        "lee@2011.cqut.edu.cn",
+        "lee.info@stanford.edu",
+        "no-reply.cs@stanford.edu",
+        "hr@stanford.edu",
+    ] {
+        // The dataset and the role accounts are loaded on first use
+        let validation = rswot::validate(email);
+        assert_eq!(allocations(|| rswot::validate(email)), 1, "{}", email);
+        if let Ok(validation) = validation {
+            let user = validation.email.user();
+            assert_eq!(
+                allocations(|| rswot::RoleAccounts::builtin().is_role_account(user)),
+                0,
+                "{}",
+                email
+            );
+        }
+    }
+}